@@ -0,0 +1,40 @@
+//! Archived copies of stories, for when the page of a story is gone or behind a paywall.
+
+use crate::config::ArchiveConfig;
+use crate::settings::settings;
+
+fn config() -> &'static ArchiveConfig {
+    &settings().archive
+}
+
+/// The archives that keep copies of pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Archive {
+    Wayback,
+    ArchiveToday,
+}
+
+impl Archive {
+    /// The archives in the order they are tried in when a page cannot be loaded.
+    pub(crate) const ALL: [Archive; 2] = [Archive::Wayback, Archive::ArchiveToday];
+
+    /// Returns the url of the most recent copy of `url` in the archive.
+    pub(crate) fn url(&self, url: &str) -> String {
+        match self {
+            Archive::Wayback => {
+                format!("{}/{url}", config().wayback_url.trim_end_matches('/'))
+            }
+            Archive::ArchiveToday => format!(
+                "{}/newest/{url}",
+                config().archive_today_url.trim_end_matches('/')
+            ),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Archive::Wayback => "the Wayback Machine",
+            Archive::ArchiveToday => "archive.today",
+        }
+    }
+}
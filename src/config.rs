@@ -0,0 +1,261 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::merged::Ranking;
+use crate::source::Source;
+
+/// The configuration of the program, read from `config.toml` in the configuration directory.
+///
+/// Every setting has a default, so the file and any of its sections may be left out.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    pub(crate) site: SiteConfig,
+    pub(crate) session: SessionConfig,
+    pub(crate) http: HttpConfig,
+    pub(crate) archive: ArchiveConfig,
+    pub(crate) hacker_news: HackerNewsConfig,
+    pub(crate) merged: MergedConfig,
+    pub(crate) discussions: DiscussionsConfig,
+    /// Saved filters for lists of stories, by name.
+    pub(crate) filters: HashMap<String, String>,
+    pub(crate) words: WordsConfig,
+}
+
+/// Settings for the site that is read.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SiteConfig {
+    /// The base URL of the site, without a trailing slash. Pointing it at a local stand-in of the
+    /// site allows trying things out without touching the real one.
+    pub(crate) url: String,
+    /// The source of the front page.
+    pub(crate) source: Source,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://lobste.rs".to_string(),
+            source: Source::Lobsters,
+        }
+    }
+}
+
+/// Settings for reading Hacker News.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct HackerNewsConfig {
+    /// The base URL of Hacker News.
+    pub(crate) url: String,
+}
+
+impl Default for HackerNewsConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://news.ycombinator.com".to_string(),
+        }
+    }
+}
+
+/// Settings for the merged front page, which is selected with the source `merged`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct MergedConfig {
+    /// The sources whose front pages are merged. When a story is on several of them, it is listed
+    /// once, under the source that comes first here.
+    pub(crate) sources: Vec<Source>,
+    /// The order of the merged stories.
+    pub(crate) ranking: Ranking,
+}
+
+impl Default for MergedConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![Source::Lobsters, Source::HackerNews],
+            ranking: Ranking::default(),
+        }
+    }
+}
+
+/// Settings for looking up other discussions of the page a story links to, which are shown above
+/// its comments.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct DiscussionsConfig {
+    /// Whether to look up other discussions at all.
+    pub(crate) enabled: bool,
+    /// Whether to also look them up on Hacker News, which sends the url of the story to its
+    /// search API.
+    pub(crate) hacker_news: bool,
+    /// The base URL of the search API of Hacker News.
+    pub(crate) hacker_news_search_url: String,
+}
+
+impl Default for DiscussionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hacker_news: false,
+            hacker_news_search_url: "https://hn.algolia.com/api/v1".to_string(),
+        }
+    }
+}
+
+/// Words to watch for and to mute. An entry is a word or phrase that is matched regardless of
+/// case, or a regular expression between slashes, such as `/wasm(time)?/`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct WordsConfig {
+    /// Words that are highlighted in titles, tags and comments.
+    pub(crate) watch: Vec<String>,
+    /// Words that hide the stories with them in the title from the list.
+    pub(crate) mute: Vec<String>,
+}
+
+/// Settings for the archives that copies of stories are read from.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ArchiveConfig {
+    /// The base URL of the Wayback Machine, to which the url of a story is appended.
+    pub(crate) wayback_url: String,
+    /// The base URL of archive.today.
+    pub(crate) archive_today_url: String,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            wayback_url: "https://web.archive.org/web".to_string(),
+            archive_today_url: "https://archive.today".to_string(),
+        }
+    }
+}
+
+/// Settings for logging in to the site.
+///
+/// Logging in works by sending the session cookie of a browser in which the user is logged in.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SessionConfig {
+    /// The value of the session cookie.
+    pub(crate) cookie: Option<String>,
+    /// A file to read the session cookie from, either in the Netscape `cookies.txt` format that
+    /// browser extensions export, or containing just the value of the cookie.
+    pub(crate) cookie_file: Option<PathBuf>,
+    /// How often to check for unread replies in the background, in seconds. Zero turns checking
+    /// off, in which case the number of unread replies is only loaded at startup.
+    pub(crate) poll_replies_secs: u64,
+}
+
+/// The name of the session cookie of the site.
+pub(crate) const SESSION_COOKIE: &str = "lobster_trap";
+
+impl SessionConfig {
+    /// Returns the value of the session cookie, if one is configured.
+    pub(crate) fn cookie(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        if let Some(cookie) = &self.cookie {
+            return Ok(Some(cookie.trim().to_string()));
+        }
+        let path = match &self.cookie_file {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read cookie file {}: {err}", path.display()))?;
+        // In the Netscape format, every line holds a cookie as seven tab-separated fields, of
+        // which the last two are its name and value. Lines starting with '#' are comments.
+        let netscape = contents
+            .lines()
+            .filter(|line| !line.starts_with('#') || line.starts_with("#HttpOnly_"))
+            .map(|line| line.split('\t').collect::<Vec<_>>())
+            .find(|fields| fields.len() == 7 && fields[5] == SESSION_COOKIE)
+            .map(|fields| fields[6].trim().to_string());
+        match netscape {
+            Some(cookie) => Ok(Some(cookie)),
+            None if contents.contains('\t') => Err(format!(
+                "no {SESSION_COOKIE} cookie in cookie file {}",
+                path.display()
+            )
+            .into()),
+            None if contents.trim().is_empty() => {
+                Err(format!("cookie file {} is empty", path.display()).into())
+            }
+            None => Ok(Some(contents.trim().to_string())),
+        }
+    }
+}
+
+/// Settings for the requests to the site.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct HttpConfig {
+    /// The number of times a failed request is retried before giving up.
+    pub(crate) retries: u32,
+    /// The time to wait before the first retry, in milliseconds. The wait doubles on every
+    /// following retry, unless the server asks for a specific wait with `Retry-After`.
+    pub(crate) retry_backoff_ms: u64,
+    /// The longest wait asked for with `Retry-After` that is honoured, in seconds. A request that
+    /// would have to wait longer fails instead.
+    pub(crate) max_retry_after_secs: u64,
+    /// The time to wait for a connection to be made, in seconds.
+    pub(crate) connect_timeout_secs: u64,
+    /// The time to wait for a whole request to complete, in seconds.
+    pub(crate) timeout_secs: u64,
+    /// The shortest time between the start of two requests, in milliseconds.
+    pub(crate) min_interval_ms: u64,
+    /// The URL of a proxy to send all requests through. If it is not set, the proxies in the
+    /// `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables are used.
+    pub(crate) proxy: Option<String>,
+    /// The `User-Agent` header sent with every request.
+    pub(crate) user_agent: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            retry_backoff_ms: 500,
+            max_retry_after_secs: 60,
+            connect_timeout_secs: 10,
+            timeout_secs: 30,
+            min_interval_ms: 250,
+            proxy: None,
+            user_agent: concat!(
+                "kreeftje/",
+                env!("CARGO_PKG_VERSION"),
+                " (+https://github.com/koenwestendorp/kreeftje)"
+            )
+            .to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the path of the configuration file.
+    ///
+    /// This is `$KREEFTJE_CONFIG` if it is set, and `kreeftje/config.toml` in
+    /// `$XDG_CONFIG_HOME` or `~/.config` otherwise.
+    pub(crate) fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("KREEFTJE_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(config_home.join("kreeftje").join("config.toml"))
+    }
+
+    /// Reads the configuration file. A missing file results in the default configuration.
+    pub(crate) fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Self::default()),
+        };
+        let contents = std::fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .map_err(|err| format!("invalid configuration in {}: {err}", path.display()).into())
+    }
+}
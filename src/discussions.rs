@@ -0,0 +1,147 @@
+//! Looking up other discussions of the page a story links to, on lobste.rs and Hacker News.
+
+use reqwest::Url;
+use scraper::Selector;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use crate::config::DiscussionsConfig;
+use crate::http::{base_url, get_json, has_session, post_form};
+use crate::listing::{Listing, SiteSearch, What};
+use crate::merged::normalize_url;
+use crate::settings::settings;
+use crate::source::Source;
+use crate::story::Story;
+
+/// The discussions of a page that were found, or why they could not be looked up.
+type Found = Result<Vec<Story>, String>;
+
+/// The discussions that were looked up, by the normalized url they are about.
+static CACHE: LazyLock<Mutex<HashMap<String, Found>>> = LazyLock::new(Default::default);
+
+fn config() -> &'static DiscussionsConfig {
+    &settings().discussions
+}
+
+/// A story found with the search API of Hacker News.
+#[derive(Debug, Deserialize)]
+pub(crate) struct HackerNewsHit {
+    #[serde(rename = "objectID")]
+    pub(crate) id: String,
+    pub(crate) title: Option<String>,
+    pub(crate) url: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) points: Option<usize>,
+    pub(crate) num_comments: Option<usize>,
+    /// When the story was submitted, as a Unix timestamp.
+    pub(crate) created_at_i: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HackerNewsResults {
+    hits: Vec<HackerNewsHit>,
+}
+
+/// Returns the discussions of the page at `url` on lobste.rs and, if it is turned on, Hacker News.
+/// They are looked up once, and kept for when they are asked for again, as is the error when they
+/// cannot be looked up. Returns nothing when looking up discussions is turned off.
+pub(crate) fn find(url: &str) -> Found {
+    let config = config();
+    if !config.enabled {
+        return Ok(Vec::new());
+    }
+    let key = normalize_url(url);
+    if let Some(found) = CACHE.lock().unwrap().get(&key) {
+        return found.clone();
+    }
+
+    let found = look_up(url, config).map_err(|err| err.to_string());
+    let found = found.map(|mut found| {
+        // The searches may also find other pages, such as others on the same domain.
+        found.retain(|story| normalize_url(story.url()) == key);
+        found
+    });
+    CACHE.lock().unwrap().insert(key, found.clone());
+    found
+}
+
+/// Looks up the discussions of the page at `url` on the sites that are turned on in `config`.
+fn look_up(
+    url: &str,
+    config: &DiscussionsConfig,
+) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+    let mut found = lobsters_submissions(url)?;
+    if config.hacker_news {
+        found.extend(hacker_news_submissions(url)?);
+    }
+    Ok(found)
+}
+
+/// Gets the stories that were submitted to lobste.rs with `url`.
+///
+/// The site only looks up a url exactly for a logged in user. Otherwise, the stories on the domain
+/// of the url are searched, of which the most recent ones are found.
+pub(crate) fn lobsters_submissions(url: &str) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+    if has_session() {
+        let html = post_form(
+            format!("{}/stories/check_url_dupe", base_url()),
+            &[("story[url]", url)],
+        )?;
+        let selector = Selector::parse(".story > .story_liner").unwrap();
+        return Ok(html
+            .select(&selector)
+            .filter_map(Story::from_html)
+            .collect());
+    }
+    let domain = match Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+    {
+        Some(domain) => domain,
+        None => return Ok(Vec::new()),
+    };
+    let query = format!("domain:{} order:newest", domain.trim_start_matches("www."));
+    match SiteSearch::parse(&query, What::Stories) {
+        Some(search) => Source::Lobsters.get_stories(&Listing::Search(search), 1),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Gets the stories that were submitted to Hacker News with `url`, through its search API.
+fn hacker_news_submissions(url: &str) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+    let endpoint = Url::parse_with_params(
+        &format!(
+            "{}/search",
+            config().hacker_news_search_url.trim_end_matches('/')
+        ),
+        &[
+            ("query", url),
+            ("restrictSearchableAttributes", "url"),
+            ("tags", "story"),
+        ],
+    )?;
+    let results: HackerNewsResults = get_json(endpoint.to_string())?;
+    Ok(results
+        .hits
+        .into_iter()
+        .filter_map(Story::from_hacker_news_hit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stand_in;
+
+    #[test]
+    fn a_failed_lookup_is_kept() {
+        // Nothing answers the search of Hacker News for this page.
+        let url = "https://www.example.com/discussions/unknown";
+        stand_in();
+        let err = find(url).unwrap_err();
+        let cached = CACHE.lock().unwrap().get(&normalize_url(url)).cloned();
+        assert_eq!(cached.map(|found| found.unwrap_err()), Some(err.clone()));
+        assert_eq!(find(url).unwrap_err(), err);
+    }
+}
@@ -0,0 +1,53 @@
+use std::io::Write;
+use std::process::Command;
+
+/// The line that separates the text that is written from the information below it.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Lets the user write a text in their editor, `$VISUAL` or `$EDITOR`, falling back to `vi`.
+///
+/// The file starts out with `context` commented out below a scissors line, as git does for commit
+/// messages. Everything from that line on is left out of the text. Returns `None` if the text is
+/// empty, which is how the user cancels.
+pub(crate) fn write(context: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let mut file = tempfile::Builder::new()
+        .prefix("kreeftje-")
+        .suffix(".md")
+        .tempfile()?;
+    writeln!(file, "\n\n{SCISSORS}")?;
+    writeln!(
+        file,
+        "# Write above this line. Everything from it on is left out, and an empty text is not sent."
+    )?;
+    for line in context.lines() {
+        writeln!(file, "{}", format!("# {line}").trim_end())?;
+    }
+    file.flush()?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor is run through the shell, such that it may include arguments.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(file.path())
+        .status()
+        .map_err(|err| format!("cannot start editor {editor:?}: {err}"))?;
+    if !status.success() {
+        return Err(format!("editor {editor:?} exited with {status}").into());
+    }
+
+    let contents = std::fs::read_to_string(file.path())?;
+    let text = match contents.split_once(SCISSORS) {
+        Some((text, _)) => text,
+        None => &contents,
+    };
+    let text = text.trim();
+    Ok(if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    })
+}
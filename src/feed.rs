@@ -0,0 +1,228 @@
+//! Reading RSS and Atom feeds as lists of stories.
+
+use roxmltree::{Document, Node};
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::OffsetDateTime;
+
+use crate::http::get_text;
+use crate::source::Source;
+use crate::story::Story;
+
+/// What is read from an item of a feed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FeedItem {
+    pub(crate) title: String,
+    pub(crate) link: String,
+    pub(crate) author: Option<String>,
+    /// How long ago the item was published, such as "3 hours ago".
+    pub(crate) time: Option<String>,
+    /// The url of the comment thread of the item.
+    pub(crate) comments: Option<String>,
+}
+
+/// Returns the name a feed is shown with: its url without the scheme.
+pub(crate) fn name(url: &str) -> &str {
+    let name = url.split_once("://").map_or(url, |(_, rest)| rest);
+    name.trim_end_matches('/')
+}
+
+/// Gets the items of the feed at `url` as stories of `source`.
+pub(crate) fn get_stories(
+    source: &Source,
+    url: String,
+) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+    let text = get_text(url.clone())?;
+    let items = parse(&text, OffsetDateTime::now_utc())
+        .map_err(|err| format!("cannot read the feed at {url}: {err}"))?;
+    Ok(items
+        .into_iter()
+        .map(|item| Story::from_feed_item(source.clone(), item))
+        .collect())
+}
+
+/// Parses the items of an RSS (1.0 or 2.0) or Atom feed, with their times as of `now`.
+fn parse(text: &str, now: OffsetDateTime) -> Result<Vec<FeedItem>, Box<dyn std::error::Error>> {
+    let document = Document::parse(text)?;
+    let root = document.root_element();
+    let items = root
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "item" | "entry"))
+        .filter_map(|node| parse_item(node, now))
+        .collect();
+    Ok(items)
+}
+
+/// Returns the trimmed text of the first child element of `node` named `name`, ignoring its
+/// namespace.
+fn child_text(node: Node, name: &str) -> Option<String> {
+    let text = node
+        .children()
+        .find(|child| child.tag_name().name() == name)?
+        .descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect::<String>();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parses an RSS item or Atom entry. Returns `None` for items without a title or link.
+fn parse_item(node: Node, now: OffsetDateTime) -> Option<FeedItem> {
+    // Atom links are in attributes, where the one without a relation, or the alternate one, is
+    // the link to the item itself. RSS links are text.
+    let link = node
+        .children()
+        .filter(|child| child.tag_name().name() == "link")
+        .find_map(
+            |link| match (link.attribute("href"), link.attribute("rel")) {
+                (Some(href), None | Some("alternate")) => Some(href.to_string()),
+                (Some(_), Some(_)) => None,
+                (None, _) => link.text().map(|text| text.trim().to_string()),
+            },
+        )
+        .filter(|link| !link.is_empty())?;
+
+    // RSS authors are often written as `email (name)`, and Atom authors have a name element.
+    let author = child_text(node, "creator")
+        .or_else(|| {
+            let author = node
+                .children()
+                .find(|child| child.tag_name().name() == "author")?;
+            child_text(author, "name").or_else(|| child_text(node, "author"))
+        })
+        .map(|author| match author.split_once('(') {
+            Some((_, name)) => name.trim_end_matches(')').trim().to_string(),
+            None => author,
+        });
+
+    let published = ["pubDate", "published", "date", "updated"]
+        .iter()
+        .filter_map(|name| child_text(node, name))
+        .find_map(|date| {
+            OffsetDateTime::parse(&date, &Rfc2822)
+                .or_else(|_| OffsetDateTime::parse(&date, &Rfc3339))
+                .ok()
+        });
+
+    Some(FeedItem {
+        title: child_text(node, "title")?,
+        link,
+        author,
+        time: published.map(|published| time_ago(published, now)),
+        comments: child_text(node, "comments"),
+    })
+}
+
+/// Describes how long before `now` the moment `then` was, the way the site does.
+pub(crate) fn time_ago(then: OffsetDateTime, now: OffsetDateTime) -> String {
+    let minutes = (now - then).whole_minutes().max(0);
+    let (number, unit) = match minutes {
+        0..=59 => (minutes, "minute"),
+        60..=1439 => (minutes / 60, "hour"),
+        1440..=43199 => (minutes / 1440, "day"),
+        43200..=525599 => (minutes / 43200, "month"),
+        _ => (minutes / 525600, "year"),
+    };
+    format!("{number} {unit}{} ago", if number == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixture;
+
+    /// The moment the feeds are read at in the tests.
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::parse("2024-03-01T12:00:00Z", &Rfc3339).unwrap()
+    }
+
+    fn items(name: &str) -> Vec<FeedItem> {
+        parse(&fixture(name), now()).unwrap()
+    }
+
+    #[test]
+    fn items_of_an_rss_2_feed() {
+        // The items without a link or a title are left out.
+        let items = items("feed_rss2.xml");
+        assert_eq!(items.len(), 2);
+
+        let item = &items[0];
+        assert_eq!(item.title, "Borrowing without tears");
+        assert_eq!(item.link, "https://www.example.com/posts/borrowing");
+        assert_eq!(item.author.as_deref(), Some("Alice Example"));
+        assert_eq!(item.time.as_deref(), Some("2 hours ago"));
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://www.example.com/posts/borrowing#comments")
+        );
+
+        let item = &items[1];
+        assert_eq!(item.title, "Lifetimes, again");
+        assert_eq!(item.link, "https://www.example.com/posts/lifetimes");
+        assert_eq!(item.author.as_deref(), Some("bob@example.com"));
+        assert_eq!(item.time, None);
+        assert_eq!(item.comments, None);
+    }
+
+    #[test]
+    fn items_of_an_rss_1_feed() {
+        let items = items("feed_rss1.xml");
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.title, "Borrowing without tears");
+        assert_eq!(item.link, "https://www.example.com/posts/borrowing");
+        assert_eq!(item.author.as_deref(), Some("Alice Example"));
+        assert_eq!(item.time.as_deref(), Some("3 hours ago"));
+    }
+
+    #[test]
+    fn entries_of_an_atom_feed() {
+        // The entry that only links to itself is left out.
+        let items = items("feed_atom.xml");
+        assert_eq!(items.len(), 2);
+
+        let item = &items[0];
+        assert_eq!(item.title, "Borrowing without tears");
+        assert_eq!(item.link, "https://www.example.com/posts/borrowing");
+        assert_eq!(item.author.as_deref(), Some("Alice Example"));
+        // The time it was published is shown rather than the time it was updated.
+        assert_eq!(item.time.as_deref(), Some("3 hours ago"));
+
+        let item = &items[1];
+        assert_eq!(item.link, "https://www.example.com/posts/lifetimes");
+        assert_eq!(item.author, None);
+        assert_eq!(item.time.as_deref(), Some("29 days ago"));
+    }
+
+    #[test]
+    fn documents_that_are_not_xml_are_an_error() {
+        assert!(parse("<html><body>Not a feed", now()).is_err());
+        assert!(parse("<html><body></body></html>", now())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn times_ago() {
+        let ago = |minutes| time_ago(now() - time::Duration::minutes(minutes), now());
+        assert_eq!(ago(-5), "0 minutes ago");
+        assert_eq!(ago(1), "1 minute ago");
+        assert_eq!(ago(59), "59 minutes ago");
+        assert_eq!(ago(60), "1 hour ago");
+        assert_eq!(ago(1439), "23 hours ago");
+        assert_eq!(ago(1440), "1 day ago");
+        assert_eq!(ago(30 * 1440 - 1), "29 days ago");
+        assert_eq!(ago(30 * 1440), "1 month ago");
+        assert_eq!(ago(365 * 1440), "1 year ago");
+        assert_eq!(ago(2 * 365 * 1440), "2 years ago");
+    }
+
+    #[test]
+    fn feed_names() {
+        assert_eq!(
+            name("https://www.example.com/atom.xml"),
+            "www.example.com/atom.xml"
+        );
+        assert_eq!(name("https://example.com/"), "example.com");
+    }
+}
@@ -0,0 +1,478 @@
+//! Filters for lists of stories, written in a small query language.
+//!
+//! A filter is a list of terms separated by spaces, all of which a story has to match:
+//!
+//! - `tag:rust`, `domain:github.com` and `by:alice` match the tags, domain and submitter. A domain
+//!   also matches its subdomains.
+//! - `score>=10`, `comments>5` and `age<1d` compare the score, the number of comments and the age
+//!   with `<`, `<=`, `>`, `>=` or `=`. An age is a number followed by `m`, `h`, `d`, `w` or `y`.
+//! - Any other word, or words in double quotes such as `"async rust"`, must be in the title.
+//! - `@name` matches the saved filter called `name`.
+//!
+//! A `-` in front of a term turns it around, such as `-tag:job`. Text matches ignore case.
+
+use std::collections::HashMap;
+
+use crate::settings::settings;
+use crate::story::Story;
+
+/// Parses the saved filters from the configuration, by name.
+pub(crate) fn parse_saved(
+    filters: &HashMap<String, String>,
+) -> Result<HashMap<String, Filter>, String> {
+    let mut saved = HashMap::new();
+    for (name, query) in filters {
+        let filter = Filter::parse_with(query, false)
+            .map_err(|err| format!("invalid filter {name:?}: {err}"))?;
+        saved.insert(name.clone(), filter);
+    }
+    Ok(saved)
+}
+
+fn saved(name: &str) -> Option<&'static Filter> {
+    settings().filters.get(name)
+}
+
+/// A parsed filter, which matches the stories that match all of its terms.
+#[derive(Debug, Clone)]
+pub(crate) struct Filter {
+    query: String,
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    condition: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Tag(String),
+    Domain(String),
+    By(String),
+    Title(String),
+    Score(Comparison, u64),
+    Comments(Comparison, u64),
+    /// The age of the story in minutes.
+    Age(Comparison, u64),
+    /// All terms of a saved filter.
+    All(Vec<Term>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl Comparison {
+    /// The operators, with the longer ones first such that `<=` is not read as `<`.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+        ("=", Comparison::Equal),
+        (":", Comparison::Equal),
+    ];
+
+    fn holds(&self, value: u64, than: u64) -> bool {
+        match self {
+            Comparison::Less => value < than,
+            Comparison::LessOrEqual => value <= than,
+            Comparison::Greater => value > than,
+            Comparison::GreaterOrEqual => value >= than,
+            Comparison::Equal => value == than,
+        }
+    }
+}
+
+impl Filter {
+    /// Parses a filter. Returns `None` if the query is empty.
+    pub(crate) fn parse(query: &str) -> Result<Option<Self>, String> {
+        if query.trim().is_empty() {
+            return Ok(None);
+        }
+        Self::parse_with(query, true).map(Some)
+    }
+
+    /// Parses a filter, in which saved filters can be used if `allow_saved` is set.
+    fn parse_with(query: &str, allow_saved: bool) -> Result<Self, String> {
+        let terms = split(query)?
+            .into_iter()
+            .map(|token| parse_term(&token, allow_saved))
+            .collect::<Result<Vec<Term>, String>>()?;
+        Ok(Self {
+            query: query.trim().to_string(),
+            terms,
+        })
+    }
+
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns whether `story` matches every term of the filter.
+    pub(crate) fn matches(&self, story: &Story) -> bool {
+        self.terms.iter().all(|term| term.matches(story))
+    }
+}
+
+impl Term {
+    fn matches(&self, story: &Story) -> bool {
+        self.condition.matches(story) != self.negated
+    }
+}
+
+impl Condition {
+    /// Returns whether `story` meets the condition. Comparisons with something the source of the
+    /// story does not tell, such as the score of a story from a feed, do not hold.
+    fn matches(&self, story: &Story) -> bool {
+        match self {
+            Condition::Tag(name) => story
+                .tags()
+                .iter()
+                .any(|tag| tag.name().eq_ignore_ascii_case(name)),
+            Condition::Domain(domain) => story.domain().is_some_and(|story_domain| {
+                let story_domain = story_domain.to_lowercase();
+                let story_domain = story_domain.trim_start_matches("www.");
+                story_domain == domain || story_domain.ends_with(&format!(".{domain}"))
+            }),
+            Condition::By(user) => story
+                .byline()
+                .user()
+                .is_some_and(|by| by.eq_ignore_ascii_case(user)),
+            Condition::Title(text) => story.title().to_lowercase().contains(text),
+            Condition::Score(comparison, than) => story
+                .votes()
+                .is_some_and(|votes| comparison.holds(votes as u64, *than)),
+            Condition::Comments(comparison, than) => story
+                .comments_number()
+                .is_some_and(|number| comparison.holds(number as u64, *than)),
+            Condition::Age(comparison, than) => story
+                .age_minutes()
+                .is_some_and(|age| comparison.holds(age, *than)),
+            Condition::All(terms) => terms.iter().all(|term| term.matches(story)),
+        }
+    }
+}
+
+/// Splits a query into its terms at the spaces that are not between double quotes. The quotes are
+/// kept.
+fn split(query: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                token.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if quoted {
+        return Err("a quote is not closed".to_string());
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(token: &str, allow_saved: bool) -> Result<Term, String> {
+    let (negated, rest) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+    if rest.is_empty() {
+        return Err("a lone '-' is not a term".to_string());
+    }
+    Ok(Term {
+        negated,
+        condition: parse_condition(rest, allow_saved)?,
+    })
+}
+
+fn parse_condition(token: &str, allow_saved: bool) -> Result<Condition, String> {
+    if token.starts_with('"') {
+        let text = token.trim_matches('"').to_lowercase();
+        return Ok(Condition::Title(text));
+    }
+    if let Some(name) = token.strip_prefix('@') {
+        if !allow_saved {
+            return Err(format!(
+                "saved filters cannot use other saved filters, as in {token:?}"
+            ));
+        }
+        return match saved(name) {
+            Some(filter) => Ok(Condition::All(filter.terms.clone())),
+            None => Err(format!("there is no saved filter called {name:?}")),
+        };
+    }
+
+    // The field is the word before the first operator. Words without one are looked for in the
+    // title.
+    let op_start = match token.find([':', '<', '>', '=']) {
+        Some(idx) => idx,
+        None => return Ok(Condition::Title(token.to_lowercase())),
+    };
+    let (field, rest) = token.split_at(op_start);
+    let (op, comparison) = Comparison::OPERATORS
+        .into_iter()
+        .find(|(op, _)| rest.starts_with(op))
+        .expect("the token has an operator here");
+    let value = rest[op.len()..].trim_matches('"');
+    if value.is_empty() {
+        return Err(format!("{token:?} has no value"));
+    }
+    let text = |make: fn(String) -> Condition| match op {
+        ":" | "=" => Ok(make(value.to_lowercase())),
+        _ => Err(format!(
+            "{field} can only be matched with ':', as in {field}:{value}"
+        )),
+    };
+    let number = || {
+        value
+            .parse::<u64>()
+            .map_err(|_| format!("{field} needs a number, not {value:?}"))
+    };
+    match field {
+        "tag" => text(Condition::Tag),
+        "domain" => text(|domain| Condition::Domain(domain.trim_start_matches("www.").to_string())),
+        "by" => text(|user| Condition::By(user.trim_start_matches('~').to_string())),
+        "title" => text(Condition::Title),
+        "score" => Ok(Condition::Score(comparison, number()?)),
+        "comments" => Ok(Condition::Comments(comparison, number()?)),
+        "age" => Ok(Condition::Age(comparison, parse_age(value)?)),
+        _ => Err(format!(
+            "unknown field {field:?}, expected tag, domain, by, title, score, comments or age"
+        )),
+    }
+}
+
+/// Parses an age such as `30m`, `12h`, `1d`, `2w` or `1y` into minutes.
+fn parse_age(value: &str) -> Result<u64, String> {
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("the age {value:?} needs a unit, such as {value}d"))?;
+    let (number, unit) = value.split_at(unit_start);
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("the age {value:?} needs a number, such as 1{unit}"))?;
+    let minutes = match unit {
+        "m" => 1,
+        "h" => 60,
+        "d" => 60 * 24,
+        "w" => 60 * 24 * 7,
+        "y" => 60 * 24 * 365,
+        _ => {
+            return Err(format!(
+                "unknown unit {unit:?} in {value:?}, expected m, h, d, w or y"
+            ))
+        }
+    };
+    Ok(number * minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::{Listing, SiteSearch, What};
+    use crate::source::Source;
+    use crate::testing::{fixture, stand_in};
+
+    fn condition(token: &str) -> Result<Condition, String> {
+        parse_condition(token, true)
+    }
+
+    /// Returns which of the stories on a saved search page match `query`, by title.
+    fn matching(query: &str) -> Vec<String> {
+        let search = SiteSearch::parse("rust order:newest", What::Stories).unwrap();
+        stand_in().serve_page(
+            "/search?q=rust&what=stories&order=newest&page=1",
+            &fixture("search_stories.html"),
+        );
+        let stories = Source::Lobsters
+            .get_stories(&Listing::Search(search), 1)
+            .unwrap();
+        let filter = Filter::parse(query).unwrap().unwrap();
+        stories
+            .iter()
+            .filter(|story| filter.matches(story))
+            .map(|story| story.title().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn splits_at_spaces_outside_quotes() {
+        assert_eq!(
+            split("tag:rust  \"async rust\" -by:alice").unwrap(),
+            ["tag:rust", "\"async rust\"", "-by:alice"]
+        );
+        assert_eq!(split("title:\"a b\"").unwrap(), ["title:\"a b\""]);
+        assert!(split("   ").unwrap().is_empty());
+        assert_eq!(split("\"async rust").unwrap_err(), "a quote is not closed");
+    }
+
+    #[test]
+    fn text_fields() {
+        assert_eq!(
+            condition("tag:Rust"),
+            Ok(Condition::Tag("rust".to_string()))
+        );
+        assert_eq!(
+            condition("tag=rust"),
+            Ok(Condition::Tag("rust".to_string()))
+        );
+        assert_eq!(
+            condition("domain:www.Example.com"),
+            Ok(Condition::Domain("example.com".to_string()))
+        );
+        assert_eq!(
+            condition("by:~Alice"),
+            Ok(Condition::By("alice".to_string()))
+        );
+        assert_eq!(
+            condition("title:\"Async Rust\""),
+            Ok(Condition::Title("async rust".to_string()))
+        );
+        // Words and quoted phrases without a field are looked for in the title.
+        assert_eq!(
+            condition("Borrow"),
+            Ok(Condition::Title("borrow".to_string()))
+        );
+        assert_eq!(
+            condition("\"Async Rust\""),
+            Ok(Condition::Title("async rust".to_string()))
+        );
+    }
+
+    #[test]
+    fn number_fields() {
+        let operators = [
+            ("<", Comparison::Less),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            (">=", Comparison::GreaterOrEqual),
+            ("=", Comparison::Equal),
+            (":", Comparison::Equal),
+        ];
+        for (op, comparison) in operators {
+            assert_eq!(
+                condition(&format!("score{op}10")),
+                Ok(Condition::Score(comparison, 10))
+            );
+            assert_eq!(
+                condition(&format!("comments{op}5")),
+                Ok(Condition::Comments(comparison, 5))
+            );
+            assert_eq!(
+                condition(&format!("age{op}2h")),
+                Ok(Condition::Age(comparison, 120))
+            );
+        }
+    }
+
+    #[test]
+    fn ages_in_minutes() {
+        assert_eq!(parse_age("30m"), Ok(30));
+        assert_eq!(parse_age("12h"), Ok(12 * 60));
+        assert_eq!(parse_age("1d"), Ok(24 * 60));
+        assert_eq!(parse_age("2w"), Ok(2 * 7 * 24 * 60));
+        assert_eq!(parse_age("1y"), Ok(365 * 24 * 60));
+        assert_eq!(
+            parse_age("5"),
+            Err("the age \"5\" needs a unit, such as 5d".to_string())
+        );
+        assert_eq!(
+            parse_age("d"),
+            Err("the age \"d\" needs a number, such as 1d".to_string())
+        );
+        assert_eq!(
+            parse_age("3x"),
+            Err("unknown unit \"x\" in \"3x\", expected m, h, d, w or y".to_string())
+        );
+    }
+
+    #[test]
+    fn negated_terms() {
+        let term = parse_term("-tag:job", true).unwrap();
+        assert!(term.negated);
+        assert_eq!(term.condition, Condition::Tag("job".to_string()));
+        assert!(!parse_term("tag:job", true).unwrap().negated);
+        assert_eq!(
+            parse_term("-", true).unwrap_err(),
+            "a lone '-' is not a term"
+        );
+    }
+
+    #[test]
+    fn bad_terms() {
+        assert_eq!(
+            condition("tag>rust").unwrap_err(),
+            "tag can only be matched with ':', as in tag:rust"
+        );
+        assert_eq!(
+            condition("score>lots").unwrap_err(),
+            "score needs a number, not \"lots\""
+        );
+        assert_eq!(
+            condition("score>=").unwrap_err(),
+            "\"score>=\" has no value"
+        );
+        assert_eq!(
+            condition("size>3").unwrap_err(),
+            "unknown field \"size\", expected tag, domain, by, title, score, comments or age"
+        );
+        assert!(Filter::parse("rust age<soon").is_err());
+        assert!(Filter::parse("  ").unwrap().is_none());
+    }
+
+    #[test]
+    fn saved_filters() {
+        stand_in();
+        let popular = saved("popular").unwrap();
+        assert_eq!(
+            condition("@popular"),
+            Ok(Condition::All(popular.terms.clone()))
+        );
+        assert_eq!(
+            condition("@missing").unwrap_err(),
+            "there is no saved filter called \"missing\""
+        );
+
+        let filters = HashMap::from([("nested".to_string(), "rust @popular".to_string())]);
+        assert_eq!(
+            parse_saved(&filters).unwrap_err(),
+            "invalid filter \"nested\": saved filters cannot use other saved filters, as in \"@popular\""
+        );
+    }
+
+    #[test]
+    fn matches_stories() {
+        let borrowing = "Borrowing without tears";
+        let weekly = "What are you doing in Rust this week?";
+        assert_eq!(matching("tag:rust"), [borrowing, weekly]);
+        assert_eq!(matching("tag:RUST -tag:ask"), [borrowing]);
+        assert_eq!(matching("domain:example.com"), [borrowing]);
+        assert_eq!(matching("by:bob"), [weekly]);
+        assert_eq!(matching("\"doing in\""), [weekly]);
+        assert_eq!(matching("score>3 comments>=12"), [borrowing]);
+        assert_eq!(matching("score<=3"), [weekly]);
+        assert_eq!(matching("age>1d"), [weekly]);
+        assert_eq!(matching("@popular"), [borrowing]);
+        assert_eq!(matching("-@popular"), [weekly]);
+        assert!(matching("rust -rust").is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use console::Term;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the alternative screen buffer is open.
+static ALTERNATE_SCREEN_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether the alternative screen buffer is open, which is the case while the interface
+/// is shown.
+pub(crate) fn alternate_screen_open() -> bool {
+    ALTERNATE_SCREEN_OPEN.load(Ordering::SeqCst)
+}
+
+/// Opens the alternative screen buffer and hides the cursor.
+fn enter_alternate_screen(term: &mut Term) -> std::io::Result<()> {
+    ALTERNATE_SCREEN_OPEN.store(true, Ordering::SeqCst);
+    term.hide_cursor()?;
+    term.write_all(b"\x1b[?1049h")?;
+    term.clear_screen()
+}
+
+/// Closes the alternative screen buffer again and shows the cursor, returning the terminal to
+/// what it looked like before the program started. Does nothing if the buffer is not open.
+fn leave_alternate_screen(term: &mut Term) -> std::io::Result<()> {
+    if !ALTERNATE_SCREEN_OPEN.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    term.write_all(b"\x1b[0m")?;
+    term.clear_screen()?;
+    term.show_cursor()?;
+    term.write_all(b"\x1b[?1049l")?;
+    term.flush()
+}
+
+/// Keeps the terminal in the alternative screen buffer for as long as it lives.
+///
+/// The terminal is restored when the guard is dropped, which includes returning early with an
+/// error. On a panic, the terminal is restored before the panic message is printed, such that the
+/// message ends up in the normal screen buffer rather than in the alternative one.
+pub(crate) struct AlternateScreen {
+    term: Term,
+}
+
+impl AlternateScreen {
+    pub(crate) fn enter(term: &Term) -> std::io::Result<Self> {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = leave_alternate_screen(&mut Term::stdout());
+            hook(info);
+        }));
+
+        let mut term = term.clone();
+        enter_alternate_screen(&mut term)?;
+        Ok(Self { term })
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        let _ = leave_alternate_screen(&mut self.term);
+    }
+}
+
+/// Leaves the alternative screen buffer while `f` runs, such as for running another program that
+/// takes over the terminal, and enters it again afterwards.
+///
+/// The screen must be drawn entirely again afterwards.
+pub(crate) fn suspended<T>(term: &Term, f: impl FnOnce() -> T) -> std::io::Result<T> {
+    let mut term = term.clone();
+    if !ALTERNATE_SCREEN_OPEN.load(Ordering::SeqCst) {
+        return Ok(f());
+    }
+    leave_alternate_screen(&mut term)?;
+    let result = f();
+    enter_alternate_screen(&mut term)?;
+    Ok(result)
+}
+
+/// The lines that are currently on the screen.
+///
+/// Drawing a new frame only writes the lines that differ from the previous frame, which keeps the
+/// screen from flickering, especially over slow connections.
+pub(crate) struct FrameBuffer {
+    lines: Vec<String>,
+    /// Whether the whole screen must be drawn on the next frame.
+    invalid: bool,
+}
+
+impl FrameBuffer {
+    pub(crate) fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            invalid: true,
+        }
+    }
+
+    /// Forces the next frame to be drawn entirely, for when something else has drawn over the
+    /// screen or the screen has been cleared.
+    pub(crate) fn invalidate(&mut self) {
+        self.invalid = true;
+    }
+
+    /// Draws `frame` on a screen of `columns` by `rows` by writing to `out`, such as the terminal,
+    /// writing only the lines that changed.
+    ///
+    /// Lines that are wider than the screen are cut off, and lines beyond the bottom of the screen
+    /// are left out.
+    pub(crate) fn draw(
+        &mut self,
+        out: &mut impl Write,
+        frame: &str,
+        columns: u16,
+        rows: u16,
+    ) -> std::io::Result<()> {
+        let lines: Vec<String> = frame
+            .lines()
+            .chain(std::iter::repeat(""))
+            .take(rows as usize)
+            .map(|line| console::truncate_str(line, columns as usize, "").into_owned())
+            .collect();
+
+        let mut written = String::new();
+        for (row, line) in lines.iter().enumerate() {
+            // The status bar on the top row is always drawn, because loading indicators are drawn
+            // over it in between frames.
+            if !self.invalid && row > 0 && self.lines.get(row) == Some(line) {
+                continue;
+            }
+            // Move to the start of the row, write the line and clear whatever is left of the
+            // previous line after it.
+            written.push_str(&format!("\x1b[{};1H{line}\x1b[0m\x1b[K", row + 1));
+        }
+        out.write_all(written.as_bytes())?;
+        out.flush()?;
+
+        self.lines = lines;
+        self.invalid = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws `frame` on a screen of 10 by 4 and returns what was written.
+    fn draw(buffer: &mut FrameBuffer, frame: &str) -> String {
+        let mut out = Vec::new();
+        buffer.draw(&mut out, frame, 10, 4).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// The escape sequences that write `line` on `row`, counting from 1.
+    fn row(row: usize, line: &str) -> String {
+        format!("\x1b[{row};1H{line}\x1b[0m\x1b[K")
+    }
+
+    #[test]
+    fn only_changed_lines_are_written() {
+        let mut buffer = FrameBuffer::new();
+        assert_eq!(
+            draw(&mut buffer, "status\none\ntwo"),
+            [row(1, "status"), row(2, "one"), row(3, "two"), row(4, "")].concat()
+        );
+        // The status bar is always written.
+        assert_eq!(
+            draw(&mut buffer, "status\none\n2"),
+            [row(1, "status"), row(3, "2")].concat()
+        );
+        assert_eq!(draw(&mut buffer, "status\none\n2"), row(1, "status"));
+        // A line that goes away is cleared.
+        assert_eq!(
+            draw(&mut buffer, "status\none"),
+            [row(1, "status"), row(3, "")].concat()
+        );
+    }
+
+    #[test]
+    fn invalidated_frames_are_written_entirely() {
+        let mut buffer = FrameBuffer::new();
+        draw(&mut buffer, "status\none");
+        buffer.invalidate();
+        assert_eq!(
+            draw(&mut buffer, "status\none"),
+            [row(1, "status"), row(2, "one"), row(3, ""), row(4, "")].concat()
+        );
+    }
+
+    #[test]
+    fn frames_are_cut_to_the_screen() {
+        let mut buffer = FrameBuffer::new();
+        assert_eq!(
+            draw(&mut buffer, "a status bar\n1\n2\n3\n4"),
+            [row(1, "a status b"), row(2, "1"), row(3, "2"), row(4, "3")].concat()
+        );
+    }
+}
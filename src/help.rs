@@ -0,0 +1,148 @@
+use console::{style, Term};
+use std::io::Write;
+
+/// The key bindings, grouped by the screen or mode they apply to.
+const BINDINGS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Everywhere",
+        &[
+            ("q, ctrl-C", "quit"),
+            ("?", "show this help"),
+            ("s, S", "search lobste.rs for stories or comments"),
+            ("u", "open the profile of the selected user"),
+            ("H", "open your hidden stories"),
+            ("v, V", "open the stories or comments you upvoted"),
+            ("i", "open the replies to you"),
+            ("m", "open your private messages"),
+            ("Backspace, Esc", "go back to the previous screen"),
+            ("r", "retry loading after an error"),
+        ],
+    ),
+    (
+        "Stories list",
+        &[
+            ("j, k, ↓, ↑", "select the next or previous story"),
+            ("J, K", "go to the next or previous page"),
+            ("g, G", "go to the top or bottom of the list"),
+            ("l, →, c, Tab", "open the comments of the story"),
+            ("a", "read the story in the terminal"),
+            ("w, W", "open the Wayback Machine or archive.today copy"),
+            ("d", "open the next discussion of the story"),
+            ("o, Enter", "open the story in the browser"),
+            ("+, x, b", "upvote, hide or save the story, or undo it"),
+            ("F", "flag the story, or take the flag back"),
+            ("C", "comment on the story"),
+            ("/", "search the loaded stories"),
+            ("f", "filter the stories, such as tag:rust score>=10"),
+            ("M, D", "mute or unmute the submitter or the domain"),
+            ("e", "show a muted story, or collapse it again"),
+            ("n, N", "jump to the next or previous search hit"),
+        ],
+    ),
+    (
+        "Comments view",
+        &[
+            ("j, k, ↓, ↑", "scroll down or up"),
+            ("J, K", "go to the next or previous story"),
+            ("g, G", "go to the top or bottom of the comments"),
+            ("h, ←, c, Tab", "go back to the stories list"),
+            ("d", "switch to the next discussion of the story"),
+            ("R, C", "reply to the top comment, or comment on the story"),
+            ("M", "mute or unmute the author of the top comment"),
+            ("e", "show the topmost muted comment, or collapse it again"),
+            ("/", "search the comments"),
+            ("n, N", "jump to the next or previous search hit"),
+        ],
+    ),
+    (
+        "Reader",
+        &[
+            ("j, k, ↓, ↑", "scroll down or up"),
+            ("J, K", "go to the next or previous story"),
+            ("g, G", "go to the top or bottom of the article"),
+            ("a, h, ←", "go back to the stories list"),
+            ("c, Tab", "open the comments of the story"),
+            ("w, W", "read the Wayback Machine or archive.today copy"),
+            ("o", "open the story in the browser"),
+        ],
+    ),
+    (
+        "Comment lists",
+        &[
+            ("j, k, ↓, ↑", "select the next or previous comment"),
+            ("l, →, Enter", "open the thread of the comment"),
+            ("o", "open the thread in the browser"),
+        ],
+    ),
+    ("Profile", &[("t", "switch between stories and comments")]),
+    (
+        "Messages",
+        &[
+            (
+                "j, k, ↓, ↑",
+                "select the next or previous message, or scroll it",
+            ),
+            ("l, →, Enter", "open the message"),
+            ("h, ←", "close the message"),
+            ("t", "switch between the inbox and sent messages"),
+            ("R", "reply to the message"),
+        ],
+    ),
+];
+
+/// Renders the key bindings to lines.
+fn help_lines() -> Vec<String> {
+    let key_width = BINDINGS
+        .iter()
+        .flat_map(|(_, bindings)| bindings.iter())
+        .map(|(keys, _)| console::measure_text_width(keys))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (idx, (mode, bindings)) in BINDINGS.iter().enumerate() {
+        if idx > 0 {
+            lines.push(String::new());
+        }
+        lines.push(style(mode).bold().to_string());
+        for (keys, description) in bindings.iter() {
+            lines.push(format!(
+                "  {}  {description}",
+                console::pad_str(keys, key_width, console::Alignment::Left, None)
+            ));
+        }
+    }
+    lines.push(String::new());
+    lines.push(style("Press any key to close this help.").dim().to_string());
+    lines
+}
+
+/// Draws the help overlay in a box on top of whatever is on the screen.
+pub(crate) fn draw_help(term: &mut Term, columns: u16, rows: u16) -> std::io::Result<()> {
+    let lines = help_lines();
+    let inner_width = lines
+        .iter()
+        .map(|line| console::measure_text_width(line))
+        .max()
+        .unwrap_or(0)
+        .min((columns as usize).saturating_sub(4));
+    // The box needs at least its top and bottom border.
+    let height = (lines.len() + 2).min(rows as usize);
+    if height < 2 {
+        return Ok(());
+    }
+    let left = (columns as usize).saturating_sub(inner_width + 4) / 2;
+    let top = (rows as usize).saturating_sub(height) / 2;
+
+    let border = |l, r| format!("{l}{}{r}", "─".repeat(inner_width + 2));
+    term.move_cursor_to(left, top)?;
+    term.write_all(border('┌', '┐').as_bytes())?;
+    for (row, line) in lines.iter().take(height - 2).enumerate() {
+        term.move_cursor_to(left, top + 1 + row)?;
+        let line = console::pad_str(line, inner_width, console::Alignment::Left, Some("…"));
+        term.write_fmt(format_args!("│ {line} │"))?;
+    }
+    term.move_cursor_to(left, top + height - 1)?;
+    term.write_all(border('└', '┘').as_bytes())?;
+    term.flush()
+}
@@ -0,0 +1,284 @@
+use console::style;
+use scraper::{ElementRef, Html, Selector};
+use std::time::Instant;
+
+use crate::hits_page_size;
+use crate::http::{base_url, get_page};
+use crate::listing::Listing;
+use crate::status::Status;
+use crate::story::{prepend_string, Comment};
+use crate::view::Travel;
+
+/// The number of lines of a comment shown in a list of hits.
+const HIT_LINES: usize = 4;
+
+/// Selects the comments at the top of a list of comments, without the replies nested in them.
+pub(crate) const HITS_SELECTOR: &str = "#inside > ol.comments > li.comments_subtree";
+
+/// A comment in a list of comments, such as the comments results of a site search, together
+/// with the story it was posted on.
+#[derive(Debug, Clone)]
+pub(crate) struct CommentHit {
+    comment: Comment,
+    story_title: String,
+    thread_url: String,
+    /// Whether the comment is a reply the logged in user has not seen yet.
+    unread: bool,
+}
+
+impl CommentHit {
+    /// Parses a comment from a list of comments, given its `li.comments_subtree`. Returns `None`
+    /// if the comment does not link to the thread it is in.
+    ///
+    /// The comment itself shares its DOM with the comments in a thread.
+    pub(crate) fn from_html(html: ElementRef) -> Option<Self> {
+        // The last link to a story in the byline is the one to the thread the comment is in.
+        let story_link = html
+            .select(&Selector::parse(".comment .details .byline a").unwrap())
+            .filter(|a| a.value().attr("href").unwrap_or("").starts_with("/s/"))
+            .last()?;
+        let thread_path = story_link.value().attr("href")?;
+
+        let unread = html
+            .select(&Selector::parse(".comment").unwrap())
+            .next()
+            .is_some_and(|comment| comment.value().classes().any(|c| c.contains("unread")));
+
+        Some(Self {
+            unread,
+            comment: Comment::from_html(html),
+            story_title: story_link.text().collect::<String>().trim().to_string(),
+            thread_url: format!("{}{thread_path}", base_url()),
+        })
+    }
+
+    pub(crate) fn thread_url(&self) -> &String {
+        &self.thread_url
+    }
+
+    pub(crate) fn comment(&self) -> &Comment {
+        &self.comment
+    }
+
+    fn to_string(&self, width: usize, selected: bool) -> String {
+        let title = format!("on {}", self.story_title);
+        let title = if selected {
+            style(title).reverse().to_string()
+        } else {
+            style(title).bold().to_string()
+        };
+        let title = if self.unread {
+            format!("{} {title}", style("new").red().bold())
+        } else {
+            title
+        };
+        let comment = self
+            .comment
+            .to_string(width)
+            .lines()
+            .take(HIT_LINES)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        format!("{title}\n{comment}")
+    }
+}
+
+/// Gets a page of comment hits of a listing.
+fn get_hits(listing: &Listing, page: usize) -> Result<Vec<CommentHit>, Box<dyn std::error::Error>> {
+    let url = match listing.url(page) {
+        Some(url) => url,
+        None => return Ok(Vec::new()),
+    };
+    let html = get_page(url)?;
+    Ok(hits_from_html(&html))
+}
+
+/// Parses the comments of a list of comments. Only the comments at the top are hits, the replies to
+/// them are part of the comments they reply to.
+fn hits_from_html(html: &Html) -> Vec<CommentHit> {
+    let hits_selector = Selector::parse(HITS_SELECTOR).unwrap();
+    let hits_list = html.select(&hits_selector);
+    hits_list.filter_map(CommentHit::from_html).collect()
+}
+
+/// A view of a list of comments that link to their threads.
+pub(crate) struct HitsView {
+    listing: Listing,
+    hits: Vec<CommentHit>,
+    site_pages_loaded: usize,
+    exhausted: bool,
+    /// The error that occurred when loading the last page, until a retry is requested.
+    error: Option<String>,
+    loaded_at: Option<Instant>,
+    pos: usize,
+    page_size: usize,
+}
+
+impl HitsView {
+    /// Creates a new empty view of a listing of comments.
+    ///
+    /// The view shows a single comment per page until [`HitsView::resize`] is called.
+    pub(crate) fn new(listing: Listing) -> Self {
+        Self {
+            listing,
+            hits: Vec::new(),
+            site_pages_loaded: 0,
+            exhausted: false,
+            error: None,
+            loaded_at: None,
+            pos: 0,
+            page_size: 1,
+        }
+    }
+
+    /// Sets the number of rows the view is drawn in.
+    pub(crate) fn resize(&mut self, height: u16) {
+        self.page_size = hits_page_size(height);
+    }
+
+    /// Loads what is needed to show the view. If that fails, the error is kept and shown in the
+    /// status bar, and no further loading is attempted until [`HitsView::retry`] is called.
+    pub(crate) fn load(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(err) = self.load_hits_next_page() {
+            self.error = Some(err.to_string());
+        }
+    }
+
+    /// Allows loading to be attempted again after an error.
+    pub(crate) fn retry(&mut self) {
+        self.error = None;
+    }
+
+    /// Get new pages of hits until the page that `pos` is on can be filled, or the listing has
+    /// no more hits.
+    fn load_hits_next_page(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let needed = self.pos + self.page_size - (self.pos % self.page_size);
+        while self.hits.len() < needed && !self.exhausted {
+            let hits = &mut get_hits(&self.listing, self.site_pages_loaded + 1)?;
+            if hits.is_empty() {
+                self.exhausted = true;
+            }
+            if self.site_pages_loaded == 0 {
+                self.loaded_at = Some(Instant::now());
+            }
+            self.site_pages_loaded += 1;
+            self.hits.append(hits);
+        }
+        self.pos = self.pos.min(self.hits.len().saturating_sub(1));
+
+        Ok(())
+    }
+
+    pub(crate) fn go_to(&mut self, travel: Travel) {
+        match travel {
+            Travel::NextStep => self.pos += self.page_size,
+            Travel::PrevStep => self.pos -= self.pos.min(self.page_size),
+            Travel::NextItem => self.pos += 1,
+            Travel::PrevItem => self.pos -= self.pos.min(1),
+            Travel::Top => self.pos = 0,
+            Travel::Bottom => self.pos = self.hits.len().saturating_sub(1),
+        }
+    }
+
+    pub(crate) fn get_selected_hit(&self) -> Option<&CommentHit> {
+        self.hits.get(self.pos)
+    }
+
+    /// Returns the author of the selected comment.
+    pub(crate) fn selected_user(&self) -> Option<&str> {
+        Some(self.get_selected_hit()?.comment.author())
+    }
+
+    pub(crate) fn generate_string(&self, width: u16) -> String {
+        if self.hits.is_empty() {
+            return "No comments found.".to_string();
+        }
+        let margin = 2;
+        let start = self.view_page() * self.page_size;
+        let end = self.hits.len().min(start + self.page_size);
+        self.hits[start..end]
+            .iter()
+            .enumerate()
+            .map(|(idx, hit)| {
+                prepend_string(
+                    &hit.to_string(
+                        (width as usize).saturating_sub(margin * 2),
+                        start + idx == self.pos,
+                    ),
+                    &" ".repeat(margin),
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    pub(crate) fn view_page(&self) -> usize {
+        self.pos / self.page_size
+    }
+
+    /// Returns the contents of the status bar for this view.
+    pub(crate) fn status(&self) -> Status {
+        Status {
+            position: Some((self.pos, self.hits.len())),
+            loaded_at: self.loaded_at,
+            error: self.error.as_ref().map(|err| format!("{err} (r to retry)")),
+            ..Status::new(self.listing.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{document, fixture, stand_in};
+
+    #[test]
+    fn hits_from_a_comment_search_page() {
+        let hits = hits_from_html(&document("search_comments.html"));
+        assert_eq!(hits.len(), 2);
+
+        let hit = &hits[0];
+        assert_eq!(hit.story_title, "Borrowing without tears");
+        assert_eq!(
+            hit.thread_url(),
+            &stand_in().url("/s/abc123/borrowing_without_tears")
+        );
+        assert!(!hit.unread);
+        assert_eq!(hit.comment().author(), "carol");
+        assert_eq!(hit.comment().short_id(), "xk3a9z");
+        assert_eq!(
+            hit.comment().text().trim(),
+            "The borrow checker stopped bothering me once I stopped keeping references in structs."
+        );
+
+        assert_eq!(hits[1].story_title, "A year of Rust at work");
+        assert_eq!(hits[1].comment().author(), "dave");
+    }
+
+    #[test]
+    fn replies_are_not_hits() {
+        stand_in().serve_page("/~carol/threads", &fixture("user_threads.html"));
+        let listing = Listing::UserThreads("carol".to_string());
+        let hits = get_hits(&listing, 1).unwrap();
+        let ids = hits
+            .iter()
+            .map(|hit| hit.comment().short_id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["xk3a9z", "m4n3b2"]);
+        assert_eq!(hits[1].story_title, "The state of async traits");
+    }
+
+    #[test]
+    fn comments_without_a_thread_are_skipped() {
+        let mut page = fixture("search_comments.html");
+        page = page.replacen("<a href=\"/s/abc123/borrowing_without_tears\">", "<a>", 1);
+        page = page.replacen("/s/abc123/borrowing_without_tears#c_xk3a9z", "#c_xk3a9z", 1);
+        stand_in();
+        let hits = hits_from_html(&Html::parse_document(&page));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].comment().author(), "dave");
+    }
+}
@@ -0,0 +1,416 @@
+use reqwest::blocking;
+use reqwest::header::{HeaderValue, CONTENT_TYPE, COOKIE, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use scraper::{Html, Selector};
+use serde::de::DeserializeOwned;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, SESSION_COOKIE};
+use crate::session::NOT_LOGGED_IN;
+use crate::settings::settings;
+use crate::status;
+
+/// The longest wait between two attempts of a request, however many retries are configured.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The CSRF token from the most recently loaded page that had one.
+///
+/// The site only accepts forms that are sent with a token from one of its pages, and a token stays
+/// valid for as long as the session.
+static CSRF_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+/// The client all requests are sent with, which is kept in the [settings](crate::settings).
+///
+/// Sharing one client keeps connections to the site open between requests.
+pub(crate) struct Client {
+    client: blocking::Client,
+    /// The base URL of the site.
+    base_url: String,
+    /// The session cookie, which is sent with the requests to the site.
+    session_cookie: Option<HeaderValue>,
+    retries: u32,
+    retry_backoff: Duration,
+    max_retry_after: Duration,
+    min_interval: Duration,
+    /// When the last request was started, to keep requests at least `min_interval` apart.
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl Client {
+    pub(crate) fn new(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let session_cookie = match config.session.cookie()? {
+            Some(cookie) => {
+                let mut value = HeaderValue::from_str(&format!("{SESSION_COOKIE}={cookie}"))
+                    .map_err(|_| "the session cookie contains invalid characters")?;
+                value.set_sensitive(true);
+                Some(value)
+            }
+            None => None,
+        };
+
+        let config_http = &config.http;
+        let mut builder = blocking::Client::builder()
+            .user_agent(config_http.user_agent.as_str())
+            .gzip(true)
+            .connect_timeout(Duration::from_secs(config_http.connect_timeout_secs))
+            .timeout(Duration::from_secs(config_http.timeout_secs));
+        // Without a proxy in the configuration, the client uses the proxies from the environment.
+        if let Some(proxy) = &config_http.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|err| format!("invalid proxy {proxy:?}: {err}"))?;
+            builder = builder.proxy(proxy);
+        }
+        Ok(Self {
+            client: builder.build()?,
+            base_url: config.site.url.trim_end_matches('/').to_string(),
+            session_cookie,
+            retries: config_http.retries,
+            retry_backoff: Duration::from_millis(config_http.retry_backoff_ms),
+            max_retry_after: Duration::from_secs(config_http.max_retry_after_secs),
+            min_interval: Duration::from_millis(config_http.min_interval_ms),
+            last_request: Mutex::new(None),
+        })
+    }
+
+    /// Waits until at least `min_interval` has passed since the start of the previous request.
+    fn wait_turn(&self) {
+        let mut last_request = self.last_request.lock().unwrap();
+        if let Some(last) = *last_request {
+            let next = last + self.min_interval;
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// Starts a request to `url`. Only requests to the site carry the session cookie, such that
+    /// it is not handed to the other sites articles are read from.
+    fn request(&self, method: Method, url: &str) -> blocking::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.session_cookie {
+            Some(cookie) if self.is_on_site(url) => request.header(COOKIE, cookie.clone()),
+            _ => request,
+        }
+    }
+
+    /// Returns whether `url` is that of a page of the site.
+    fn is_on_site(&self, url: &str) -> bool {
+        url.strip_prefix(self.base_url.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+    }
+
+    /// Sends a GET request for `url`, retrying when it fails in a way that may be temporary.
+    ///
+    /// Retries wait for an exponential backoff, or for as long as the server asks with
+    /// `Retry-After`.
+    fn get(&self, url: &str) -> Result<blocking::Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            self.wait_turn();
+            let wait = match self.request(Method::GET, url).send() {
+                Ok(res) if attempt < self.retries && is_retryable_status(res.status()) => {
+                    match res.headers().get(RETRY_AFTER).and_then(retry_after) {
+                        Some(wait) if wait > self.max_retry_after => return res.error_for_status(),
+                        Some(wait) => wait,
+                        None => self.backoff(attempt),
+                    }
+                }
+                Err(err) if attempt < self.retries && is_retryable_error(&err) => {
+                    self.backoff(attempt)
+                }
+                res => return res.and_then(|res| res.error_for_status()),
+            };
+            std::thread::sleep(wait);
+            attempt += 1;
+        }
+    }
+
+    /// Sends a POST request with `form` to `url`, along with the CSRF token.
+    ///
+    /// Unlike GET requests, these are not retried, because doing something on the site twice may
+    /// not be the same as doing it once.
+    fn post(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+        csrf_token: &str,
+    ) -> Result<blocking::Response, reqwest::Error> {
+        self.wait_turn();
+        let mut form = form.to_vec();
+        form.push(("authenticity_token", csrf_token));
+        self.request(Method::POST, url)
+            .header("x-csrf-token", csrf_token)
+            .form(&form)
+            .send()
+            .and_then(|res| res.error_for_status())
+    }
+
+    /// Returns the wait before retry number `attempt + 1`, which doubles on every attempt, up to
+    /// [`MAX_BACKOFF`].
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+        self.retry_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+}
+
+fn client() -> &'static Client {
+    &settings().client
+}
+
+/// Returns the base URL of the site, without a trailing slash.
+pub(crate) fn base_url() -> &'static str {
+    &client().base_url
+}
+
+/// Returns whether requests are sent with a session cookie.
+pub(crate) fn has_session() -> bool {
+    client().session_cookie.is_some()
+}
+
+/// Returns whether a response with `status` may be different when the request is sent again.
+///
+/// That is the case for errors on the side of the server and for being rate limited, but not for
+/// requests the server refuses, such as for a page that does not exist.
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Returns whether `err` is a response with an error status, such as for a page that does not
+/// exist (4xx) or from a server that fails (5xx), rather than a problem with the connection.
+pub(crate) fn is_status_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|err| err.status().is_some())
+}
+
+/// Returns whether a request that failed without a response may succeed when it is sent again,
+/// such as after a connection problem or a timeout. Requests that cannot be sent at all, such as
+/// for an invalid url, fail the same way every time.
+fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+/// Reads the wait from a `Retry-After` header with a number of seconds.
+///
+/// The header may also hold a date, which is not supported and results in `None`.
+fn retry_after(value: &HeaderValue) -> Option<Duration> {
+    let secs = value.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// Keeps the CSRF token of the page at `url` for sending forms later on, if it is a page of the
+/// site. Other sites that run the same software, such as those in the merged front page, have
+/// tokens of their own that the site does not accept.
+fn remember_csrf_token(url: &str, html: &Html) {
+    if let Some(token) = csrf_token_to_keep(url, html) {
+        *CSRF_TOKEN.lock().unwrap() = Some(token);
+    }
+}
+
+/// Returns the CSRF token of the page at `url` if it is one to keep, see [`remember_csrf_token`].
+fn csrf_token_to_keep(url: &str, html: &Html) -> Option<String> {
+    if !client().is_on_site(url) {
+        return None;
+    }
+    let selector = Selector::parse(r#"meta[name="csrf-token"]"#).unwrap();
+    let meta = html.select(&selector).next()?;
+    meta.value().attr("content").map(str::to_string)
+}
+
+/// Returns a CSRF token, loading a page to get one from if none has been seen yet.
+fn csrf_token() -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(token) = CSRF_TOKEN.lock().unwrap().clone() {
+        return Ok(token);
+    }
+    get_page(format!("{}/settings", base_url()))?;
+    CSRF_TOKEN
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "the site did not provide a CSRF token".into())
+}
+
+pub(crate) fn get_page(url: String) -> Result<Html, Box<dyn std::error::Error>> {
+    status::fetch_started(&url);
+    get_page_quietly(url)
+}
+
+/// Gets a page like [`get_page`], but without showing that it is loading, for requests in the
+/// background.
+pub(crate) fn get_page_quietly(url: String) -> Result<Html, Box<dyn std::error::Error>> {
+    let res = client().get(&url)?;
+
+    let url = res.url().to_string();
+    let html = scraper::Html::parse_document(&res.text()?);
+    remember_csrf_token(&url, &html);
+    Ok(html)
+}
+
+/// Gets a page from any site, such as the article of a story. Returns `None` when the response is
+/// not an HTML document, but for example a PDF or a video.
+///
+/// Unlike [`get_page`], the CSRF token of the page is not kept, because it is not one of the site.
+pub(crate) fn get_document(url: String) -> Result<Option<Html>, Box<dyn std::error::Error>> {
+    status::fetch_started(&url);
+    let res = client().get(&url)?;
+    // Without a content type, the response is assumed to be a page.
+    let is_html = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|value| value.contains("html"));
+    if !is_html {
+        return Ok(None);
+    }
+    Ok(Some(scraper::Html::parse_document(&res.text()?)))
+}
+
+/// Gets `url` and reads the response as text, such as for a feed.
+pub(crate) fn get_text(url: String) -> Result<String, Box<dyn std::error::Error>> {
+    status::fetch_started(&url);
+    let res = client().get(&url)?;
+    Ok(res.text()?)
+}
+
+/// Gets `url` and reads the response as JSON.
+pub(crate) fn get_json<T: DeserializeOwned>(url: String) -> Result<T, Box<dyn std::error::Error>> {
+    status::fetch_started(&url);
+    let res = client().get(&url)?;
+    Ok(res.json()?)
+}
+
+/// Sends `form` to `url` the way a form on the site would, and returns the page the site responds
+/// with.
+pub(crate) fn post_form(
+    url: String,
+    form: &[(&str, &str)],
+) -> Result<Html, Box<dyn std::error::Error>> {
+    Ok(post_form_to_page(url, form)?.1)
+}
+
+/// Sends `form` to `url` like [`post_form`], and also returns the url of the page the site
+/// responds with, which differs from `url` when the site redirects to the result.
+pub(crate) fn post_form_to_page(
+    url: String,
+    form: &[(&str, &str)],
+) -> Result<(String, Html), Box<dyn std::error::Error>> {
+    if !has_session() {
+        return Err(NOT_LOGGED_IN.into());
+    }
+    let token = csrf_token()?;
+    status::fetch_started(&url);
+    let res = match client().post(&url, form, &token) {
+        // The token may have expired, in which case the site refuses it. Try again once with a
+        // fresh one.
+        Err(err) if err.status() == Some(StatusCode::UNPROCESSABLE_ENTITY) => {
+            CSRF_TOKEN.lock().unwrap().take();
+            client().post(&url, form, &csrf_token()?)?
+        }
+        res => res?,
+    };
+
+    let url = res.url().to_string();
+    let html = scraper::Html::parse_document(&res.text()?);
+    remember_csrf_token(&url, &html);
+    Ok((url, html))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stand_in;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let client = Client::new(&Config::default()).unwrap();
+        assert_eq!(client.backoff(0), Duration::from_millis(500));
+        assert_eq!(client.backoff(3), Duration::from_millis(4000));
+        // Neither the power nor the multiplication overflow for many retries.
+        assert_eq!(client.backoff(7), MAX_BACKOFF);
+        assert_eq!(client.backoff(40), MAX_BACKOFF);
+        assert_eq!(client.backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn only_connection_problems_are_retried() {
+        let mut config = Config::default();
+        config.http.retries = 3;
+        config.http.retry_backoff_ms = 10_000;
+        let client = Client::new(&config).unwrap();
+        let started = Instant::now();
+        let err = client.get("http://[::1").unwrap_err();
+        assert!(!is_retryable_error(&err));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Neither is a request that a server hangs up on without a response.
+        config.http.retry_backoff_ms = 1;
+        let client = Client::new(&config).unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            drop(stream);
+            listener.set_nonblocking(true).unwrap();
+            std::thread::sleep(Duration::from_millis(500));
+            listener.accept().is_err()
+        });
+        assert!(client.get(&url).is_err());
+        assert!(server.join().unwrap(), "the request was sent again");
+
+        // A server that cannot be reached may be reachable again later.
+        config.http.retries = 0;
+        let client = Client::new(&config).unwrap();
+        let err = client.get("http://127.0.0.1:1/").unwrap_err();
+        assert!(is_retryable_error(&err));
+    }
+
+    #[test]
+    fn session_cookie_is_not_sent_to_lookalike_urls() {
+        let mut config = Config::default();
+        config.site.url = "https://lobste.rs".to_string();
+        config.session.cookie = Some("secret".to_string());
+        let client = Client::new(&config).unwrap();
+        let cookie = |url: &str| {
+            let request = client.request(Method::GET, url).build().unwrap();
+            request.headers().get(COOKIE).cloned()
+        };
+        let sent = Some(HeaderValue::from_static("lobster_trap=secret"));
+        assert_eq!(cookie("https://lobste.rs"), sent);
+        assert_eq!(cookie("https://lobste.rs/s/abc123"), sent);
+        assert_eq!(cookie("https://lobste.rs?page=2"), sent);
+        assert_eq!(cookie("https://lobste.rs.example.com/"), None);
+        assert_eq!(cookie("https://lobste.rsx/"), None);
+        assert_eq!(cookie("http://lobste.rs/"), None);
+        assert_eq!(cookie("https://example.com/?u=https://lobste.rs/"), None);
+    }
+
+    #[test]
+    fn csrf_token_is_only_kept_from_the_site() {
+        let page = |token: &str| {
+            format!("<html><head><meta name=\"csrf-token\" content=\"{token}\"></head></html>")
+        };
+        stand_in().serve_page("/csrf/instance", &page("instance-token"));
+        get_page(stand_in().other_url("/csrf/instance")).unwrap();
+        let kept = CSRF_TOKEN.lock().unwrap().clone();
+        assert_ne!(kept.as_deref(), Some("instance-token"));
+
+        // Other tests load pages of the site too, which may change the kept token at any time, so
+        // which token is kept is checked on the pages themselves.
+        stand_in().serve_page("/csrf/site", &page("site-token"));
+        let site = stand_in().url("/csrf/site");
+        let html = get_page(site.clone()).unwrap();
+        assert_eq!(
+            csrf_token_to_keep(&site, &html).as_deref(),
+            Some("site-token")
+        );
+        let instance = stand_in().other_url("/csrf/instance");
+        let html = get_page(instance.clone()).unwrap();
+        assert_eq!(csrf_token_to_keep(&instance, &html), None);
+        assert_eq!(
+            csrf_token_to_keep(&site, &Html::parse_document("<html></html>")),
+            None
+        );
+    }
+}
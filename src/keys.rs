@@ -0,0 +1,58 @@
+//! Reading key presses with a timeout, such that the screen can be drawn again while waiting for
+//! the next key, for example when something changed in the background.
+
+use console::{Key, Term};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// Reads key presses on a thread of its own.
+///
+/// The thread only reads a key when one is asked for, so it never takes the input that is meant for
+/// a prompt or an editor. A key that is asked for but not pressed within the timeout is returned
+/// by the next call instead.
+pub(crate) struct KeyReader {
+    requests: Sender<()>,
+    keys: Receiver<std::io::Result<Key>>,
+    /// Whether a key has been asked for that has not been returned yet.
+    waiting: bool,
+}
+
+impl KeyReader {
+    pub(crate) fn new(term: &Term) -> Self {
+        let term = term.clone();
+        let (requests, requested) = mpsc::channel::<()>();
+        let (pressed, keys) = mpsc::channel();
+        std::thread::spawn(move || {
+            for () in requested {
+                if pressed.send(term.read_key()).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            keys,
+            waiting: false,
+        }
+    }
+
+    /// Waits for a key press, for at most `timeout` if it is given. Returns `None` if no key was
+    /// pressed in time.
+    pub(crate) fn read_key(&mut self, timeout: Option<Duration>) -> std::io::Result<Option<Key>> {
+        let gone = || std::io::Error::other("the keyboard can no longer be read");
+        if !self.waiting {
+            self.requests.send(()).map_err(|_| gone())?;
+            self.waiting = true;
+        }
+        let key = match timeout {
+            Some(timeout) => match self.keys.recv_timeout(timeout) {
+                Ok(key) => key,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => return Err(gone()),
+            },
+            None => self.keys.recv().map_err(|_| gone())?,
+        };
+        self.waiting = false;
+        key.map(Some)
+    }
+}
@@ -0,0 +1,236 @@
+use reqwest::Url;
+use std::fmt::Display;
+
+use crate::http::base_url;
+use crate::STORIES_PER_SITE_PAGE;
+
+/// The number of results lobste.rs shows on one search page.
+const RESULTS_PER_SEARCH_PAGE: usize = 20;
+
+/// A paginated list of stories or comments on the site.
+#[derive(Debug, Clone)]
+pub(crate) enum Listing {
+    /// The front page.
+    Front,
+    /// The results of a site search.
+    Search(SiteSearch),
+    /// The single story of a comment thread.
+    Thread(String),
+    /// The stories submitted by a user.
+    UserStories(String),
+    /// The recent comments of a user.
+    UserThreads(String),
+    /// The stories the logged in user has hidden.
+    Hidden,
+    /// The replies to the comments and stories of the logged in user.
+    Replies,
+    /// The stories or comments the logged in user has upvoted.
+    Upvoted(What),
+}
+
+impl Listing {
+    /// Returns the url of a page of this listing, or `None` if the listing has no such page.
+    ///
+    /// ## Note
+    ///
+    /// The page numbers of the site are 1-indexed.
+    pub(crate) fn url(&self, page: usize) -> Option<String> {
+        match self {
+            Listing::Front => Some(format!("{}/page/{page}", base_url())),
+            Listing::Search(search) => Some(search.url(page)),
+            Listing::Thread(url) if page == 1 => Some(url.clone()),
+            Listing::Thread(_) => None,
+            Listing::UserStories(user) if page == 1 => {
+                Some(format!("{}/~{user}/stories", base_url()))
+            }
+            Listing::UserStories(user) => {
+                Some(format!("{}/~{user}/stories/page/{page}", base_url()))
+            }
+            Listing::UserThreads(user) if page == 1 => {
+                Some(format!("{}/~{user}/threads", base_url()))
+            }
+            Listing::UserThreads(_) => None,
+            Listing::Hidden if page == 1 => Some(format!("{}/hidden", base_url())),
+            Listing::Hidden => Some(format!("{}/hidden/page/{page}", base_url())),
+            Listing::Replies if page == 1 => Some(format!("{}/replies", base_url())),
+            Listing::Replies => Some(format!("{}/replies/page/{page}", base_url())),
+            Listing::Upvoted(What::Stories) if page == 1 => Some(format!("{}/upvoted", base_url())),
+            Listing::Upvoted(What::Stories) => Some(format!("{}/upvoted/page/{page}", base_url())),
+            Listing::Upvoted(What::Comments) if page == 1 => {
+                Some(format!("{}/upvoted/comments", base_url()))
+            }
+            Listing::Upvoted(What::Comments) => {
+                Some(format!("{}/upvoted/comments/page/{page}", base_url()))
+            }
+        }
+    }
+
+    /// The number of entries the site shows on one page of this listing.
+    pub(crate) fn per_page(&self) -> usize {
+        match self {
+            Listing::Front => STORIES_PER_SITE_PAGE,
+            Listing::Search(_) => RESULTS_PER_SEARCH_PAGE,
+            Listing::Thread(_) => 1,
+            Listing::UserStories(_) => STORIES_PER_SITE_PAGE,
+            Listing::UserThreads(_) => RESULTS_PER_SEARCH_PAGE,
+            Listing::Hidden => STORIES_PER_SITE_PAGE,
+            Listing::Replies => RESULTS_PER_SEARCH_PAGE,
+            Listing::Upvoted(What::Stories) => STORIES_PER_SITE_PAGE,
+            Listing::Upvoted(What::Comments) => RESULTS_PER_SEARCH_PAGE,
+        }
+    }
+}
+
+impl Display for Listing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Listing::Front => write!(f, "front page"),
+            Listing::Search(search) => write!(f, "{search}"),
+            Listing::Thread(_) => write!(f, "thread"),
+            Listing::UserStories(user) => write!(f, "stories by {user}"),
+            Listing::UserThreads(user) => write!(f, "comments by {user}"),
+            Listing::Hidden => write!(f, "hidden stories"),
+            Listing::Replies => write!(f, "replies"),
+            Listing::Upvoted(what) => write!(f, "upvoted {}", what.as_str()),
+        }
+    }
+}
+
+/// What kind of entries a site search looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum What {
+    Stories,
+    Comments,
+}
+
+impl What {
+    fn as_str(&self) -> &'static str {
+        match self {
+            What::Stories => "stories",
+            What::Comments => "comments",
+        }
+    }
+}
+
+/// The order of the results of a site search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Order {
+    Relevance,
+    Newest,
+    Points,
+}
+
+impl Order {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Order::Relevance => "relevance",
+            Order::Newest => "newest",
+            Order::Points => "points",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "relevance" => Some(Order::Relevance),
+            "newest" => Some(Order::Newest),
+            "points" | "score" => Some(Order::Points),
+            _ => None,
+        }
+    }
+}
+
+/// A search on lobste.rs through `/search`.
+#[derive(Debug, Clone)]
+pub(crate) struct SiteSearch {
+    query: String,
+    what: What,
+    order: Order,
+}
+
+impl SiteSearch {
+    /// Parses the input of the site search prompt.
+    ///
+    /// An `order:newest`, `order:relevance` or `order:points` term sets the order of the results
+    /// and is not part of the query. Returns `None` if the query is empty.
+    pub(crate) fn parse(input: &str, what: What) -> Option<Self> {
+        let mut order = Order::Relevance;
+        let mut terms = Vec::new();
+        for term in input.split_whitespace() {
+            match term.strip_prefix("order:").and_then(Order::from_str) {
+                Some(o) => order = o,
+                None => terms.push(term),
+            }
+        }
+        if terms.is_empty() {
+            return None;
+        }
+        Some(Self {
+            query: terms.join(" "),
+            what,
+            order,
+        })
+    }
+
+    pub(crate) fn what(&self) -> What {
+        self.what
+    }
+
+    fn url(&self, page: usize) -> String {
+        let params = [
+            ("q", self.query.as_str()),
+            ("what", self.what.as_str()),
+            ("order", self.order.as_str()),
+            ("page", &page.to_string()),
+        ];
+        Url::parse_with_params(&format!("{}/search", base_url()), &params)
+            .unwrap()
+            .to_string()
+    }
+}
+
+impl Display for SiteSearch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "search {} for \"{}\" ({})",
+            self.what.as_str(),
+            self.query,
+            self.order.as_str()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stand_in;
+
+    #[test]
+    fn search_terms_and_order() {
+        let search = SiteSearch::parse("order:newest borrow  checker", What::Comments).unwrap();
+        assert_eq!(search.query, "borrow checker");
+        assert_eq!(search.what(), What::Comments);
+        assert_eq!(search.order, Order::Newest);
+        assert_eq!(
+            SiteSearch::parse("rust", What::Stories).unwrap().order,
+            Order::Relevance
+        );
+        assert!(SiteSearch::parse(" order:points ", What::Stories).is_none());
+        // An order that does not exist is searched for.
+        let search = SiteSearch::parse("order:age", What::Stories).unwrap();
+        assert_eq!(search.query, "order:age");
+    }
+
+    #[test]
+    fn search_url() {
+        let search = SiteSearch::parse("c++ & rust order:score", What::Stories).unwrap();
+        assert_eq!(
+            search.url(2),
+            stand_in().url("/search?q=c%2B%2B+%26+rust&what=stories&order=points&page=2")
+        );
+        assert_eq!(
+            Listing::Search(search).url(1),
+            Some(stand_in().url("/search?q=c%2B%2B+%26+rust&what=stories&order=points&page=1"))
+        );
+    }
+}
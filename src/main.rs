@@ -1,141 +1,616 @@
 use console::{Key, Term};
-use reqwest::blocking;
-use scraper::{Html, Selector};
 use std::io::Write;
 
+mod archive;
+mod config;
+mod discussions;
+mod editor;
+mod feed;
+mod filter;
+mod frame;
+mod help;
+mod hits;
+mod http;
+mod keys;
+mod listing;
+mod merged;
+mod messages;
+mod mutes;
+mod pages;
+mod profile;
+mod reader;
+mod search;
+mod session;
+mod settings;
+mod source;
+mod status;
 mod story;
+mod submit;
 mod tags;
+#[cfg(test)]
+mod testing;
 mod view;
+mod words;
+mod wrap;
 
-use story::Story;
+use archive::Archive;
+use config::Config;
+use filter::Filter;
+use frame::{AlternateScreen, FrameBuffer};
+use help::draw_help;
+use hits::HitsView;
+use http::get_page;
+use keys::KeyReader;
+use listing::{Listing, SiteSearch, What};
+use messages::MessagesView;
+use profile::{ProfileTab, ProfileView};
+use search::Search;
+use session::{Session, NOT_LOGGED_IN};
+use source::Source;
+use status::Status;
+use std::str::FromStr;
+use std::time::Duration;
+use story::{FlagReason, Story, StoryAction, StoryToggle};
 use tags::Tag;
-use view::{Travel, View, ViewMode};
+use view::{Travel, View};
 
-const URL: &str = "https://lobste.rs";
-const PAGE_URL: &str = "https://lobste.rs/page";
 const STORIES_PER_SITE_PAGE: usize = 25;
 
-fn get_page(url: String) -> Result<Html, Box<dyn std::error::Error>> {
-    let client = blocking::Client::new();
-    #[cfg(debug_assertions)]
-    eprint!("loading ({url}) ...");
-    #[cfg(not(debug_assertions))]
-    eprint!("loading ...");
-    let res = client
-        .get(url)
-        .header(
-            "user-agent",
-            "Mozilla/5.0 (X11; Linux x86_64; rv:12.0) Gecko/20100101 Firefox/12.0",
-        )
-        .send()?;
-    let status = res.error_for_status_ref()?.status();
-    #[cfg(debug_assertions)]
-    eprintln!("\u{1b}[2K\rloaded ({status})");
-
-    let html = scraper::Html::parse_document(&res.text()?);
-    Ok(html)
+/// How often the screen is checked for changes from the background while waiting for a key.
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The smallest size the screens are laid out for. A smaller terminal shows the top left of the
+/// layout.
+const MIN_COLUMNS: u16 = 20;
+const MIN_ROWS: u16 = 4;
+
+/// Gets the story of a comment thread, with its comments loaded.
+fn get_thread(url: &str) -> Result<Option<Story>, Box<dyn std::error::Error>> {
+    let html = get_page(url.to_string())?;
+    Ok(Story::from_thread_html(&html))
+}
+
+/// The number of comments that fit on a page of a list of comments, given the number of rows.
+fn hits_page_size(rows: u16) -> usize {
+    (rows as usize / 7).max(1)
+}
+
+/// A screen of the program. Screens are stacked on top of each other, and going back returns to
+/// the screen below.
+enum Screen {
+    Stories(Box<View>),
+    Hits(HitsView),
+    Profile(Box<ProfileView>),
+    Messages(MessagesView),
+}
+
+impl Screen {
+    /// Returns the user whose profile can be opened from the screen.
+    fn selected_user(&self) -> Option<&str> {
+        match self {
+            Screen::Stories(view) => view.selected_user(),
+            Screen::Hits(hits) => hits.selected_user(),
+            Screen::Messages(messages) => messages.selected_user(),
+            Screen::Profile(profile) => match profile.tab() {
+                ProfileTab::Stories => profile.stories().selected_user(),
+                ProfileTab::Comments => profile.comments().selected_user(),
+            },
+        }
+    }
+
+    /// Sends an action that is already shown on the screen to the site. Returns whether there was
+    /// such an action.
+    fn perform_pending(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        match self {
+            Screen::Stories(view) => view.perform_pending(),
+            Screen::Hits(_) | Screen::Messages(_) => Ok(false),
+            Screen::Profile(profile) => profile.stories_mut().perform_pending(),
+        }
+    }
+
+    /// Allows loading what the screen shows to be attempted again after an error.
+    fn retry(&mut self) {
+        match self {
+            Screen::Stories(view) => view.retry(),
+            Screen::Hits(hits) => hits.retry(),
+            Screen::Messages(messages) => messages.retry(),
+            Screen::Profile(profile) => {
+                profile.stories_mut().retry();
+                profile.comments_mut().retry();
+            }
+        }
+    }
+}
+
+/// Shows `prompt` on the bottom line of the terminal and reads a line of input.
+fn read_prompt(term: &mut Term, frame: &mut FrameBuffer, prompt: &str) -> std::io::Result<String> {
+    // The prompt is drawn over the frame.
+    frame.invalidate();
+    let (rows, _) = term.size();
+    term.move_cursor_to(0, (rows as usize).saturating_sub(1))?;
+    term.clear_line()?;
+    term.write_all(prompt.as_bytes())?;
+    term.show_cursor()?;
+    let input = term.read_line();
+    term.hide_cursor()?;
+    input
+}
+
+/// Writes a comment in the editor and posts it on the selected story, in reply to the comment at
+/// the top of the comments view if `to_comment` is set. The thread is loaded again afterwards.
+fn reply(
+    term: &Term,
+    frame: &mut FrameBuffer,
+    view: &mut View,
+    to_comment: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !http::has_session() {
+        return Err(NOT_LOGGED_IN.into());
+    }
+    let story = view.get_selected_story().ok_or("no story selected")?;
+    let (parent, context) = if to_comment {
+        let comment = view
+            .selected_comment()
+            .ok_or("open the comments to reply to one")?;
+        let context = format!("Replying to {}:\n\n{}", comment.author(), comment.text());
+        (Some(comment.short_id().to_string()), context)
+    } else {
+        (None, format!("Commenting on \"{}\"", story.title()))
+    };
+
+    // The editor takes over the terminal.
+    frame.invalidate();
+    let text = match frame::suspended(term, || editor::write(&context))?? {
+        Some(text) => text,
+        None => return Ok(()),
+    };
+    story.post_comment(parent.as_deref(), &text)?;
+
+    if let Some(story) = view.get_selected_story_mut() {
+        story.reload()?;
+    }
+    Ok(())
+}
+
+/// Loads what is needed to show the stories view and renders it. Returns the rendered view and
+/// the contents of the status bar for the view.
+fn render_stories(view: &mut View, columns: u16, rows: u16) -> (String, Status) {
+    view.resize(columns, rows);
+    view.load();
+    (view.generate_string(), view.status())
+}
+
+/// Loads what is needed to show the list of comments and renders it. Returns the rendered view
+/// and the contents of the status bar for the view.
+fn render_hits(hits: &mut HitsView, columns: u16, rows: u16) -> (String, Status) {
+    hits.resize(rows);
+    hits.load();
+    (hits.generate_string(columns), hits.status())
+}
+
+/// Handles a key press on a stories view.
+fn handle_stories_key(
+    term: &mut Term,
+    frame: &mut FrameBuffer,
+    view: &mut View,
+    input: Key,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match input {
+        // J — vv
+        // Load next page.
+        Key::Char('J') => view.go_to(Travel::NextStep),
+        // K — ^^
+        // Load previous page.
+        Key::Char('K') => view.go_to(Travel::PrevStep),
+        // j — v
+        // Select next story.
+        Key::Char('j') | Key::ArrowDown => view.go_to(Travel::NextItem),
+        // k — ^
+        // Select other story.
+        Key::Char('k') | Key::ArrowUp => view.go_to(Travel::PrevItem),
+        // l — >
+        // Open comments.
+        Key::Char('l') | Key::ArrowRight => view.view_comments(),
+        // h — <
+        // Close comments.
+        Key::Char('h') | Key::ArrowLeft => view.view_list(),
+        // Toggle comments.
+        Key::Char('c') | Key::Tab => view.view_toggle(),
+        // Toggle the reader.
+        Key::Char('a') => view.view_reader_toggle(),
+        // Open or read the copy of the story in the Wayback Machine or archive.today.
+        Key::Char('w') => view.open_archived(Archive::Wayback)?,
+        Key::Char('W') => view.open_archived(Archive::ArchiveToday)?,
+        // Switch to the discussion of the story on another source of the merged front page.
+        Key::Char('d') => view.next_discussion()?,
+        // g — ^^
+        // Go to first page.
+        Key::Char('g') => view.go_to(Travel::Top),
+        // G — vv
+        // Go to last loaded page.
+        Key::Char('G') => view.go_to(Travel::Bottom),
+        // Search the stories list or comments.
+        Key::Char('/') => {
+            let query = read_prompt(term, frame, "/")?;
+            view.search(Search::new(&query));
+        }
+        // Filter the stories list.
+        Key::Char('f') => {
+            let query = read_prompt(term, frame, "filter: ")?;
+            view.set_filter(Filter::parse(&query)?);
+        }
+        // Jump to the next and previous search hit.
+        Key::Char('n') => view.search_next(true),
+        Key::Char('N') => view.search_next(false),
+        // Upvote, hide or save the selected story, or undo that.
+        Key::Char('+') | Key::Char('x') | Key::Char('b') => {
+            if !http::has_session() {
+                return Err(NOT_LOGGED_IN.into());
+            }
+            view.toggle(match input {
+                Key::Char('+') => StoryToggle::Vote,
+                Key::Char('x') => StoryToggle::Hide,
+                _ => StoryToggle::Save,
+            });
+        }
+        // Flag the selected story, or take the flag back.
+        Key::Char('F') => {
+            if !http::has_session() {
+                return Err(NOT_LOGGED_IN.into());
+            }
+            if view.get_selected_story().is_some_and(Story::flagged) {
+                view.act(StoryAction::Unflag);
+            } else if let Some(reason) =
+                FlagReason::parse(&read_prompt(term, frame, FlagReason::PROMPT)?)?
+            {
+                view.act(StoryAction::Flag(reason));
+            }
+        }
+        // Mute or unmute the submitter of the story or the author of the comment at the top, or the
+        // domain of the story.
+        Key::Char('M') => view.toggle_mute_user()?,
+        Key::Char('D') => view.toggle_mute_domain()?,
+        // Show a muted story or comment, or collapse it again.
+        Key::Char('e') => view.toggle_muted()?,
+        // Reply to the comment at the top of the comments view, or comment on the story.
+        Key::Char('R') => reply(term, frame, view, true)?,
+        Key::Char('C') => reply(term, frame, view, false)?,
+        // Open link in browser.
+        Key::Char('o') | Key::Enter => {
+            if let Some(story) = view.get_selected_story() {
+                webbrowser::open(story.url())?
+            }
+        }
+
+        // Otherwise, do nothing
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handles a key press on a list of comments. Returns the screen of a thread if one is opened.
+fn handle_hits_key(
+    hits: &mut HitsView,
+    input: Key,
+) -> Result<Option<Screen>, Box<dyn std::error::Error>> {
+    let mut next_screen = None;
+    match input {
+        Key::Char('J') => hits.go_to(Travel::NextStep),
+        Key::Char('K') => hits.go_to(Travel::PrevStep),
+        Key::Char('j') | Key::ArrowDown => hits.go_to(Travel::NextItem),
+        Key::Char('k') | Key::ArrowUp => hits.go_to(Travel::PrevItem),
+        Key::Char('g') => hits.go_to(Travel::Top),
+        Key::Char('G') => hits.go_to(Travel::Bottom),
+        // Open the thread the comment was posted in.
+        Key::Char('l') | Key::ArrowRight | Key::Enter => {
+            if let Some(hit) = hits.get_selected_hit() {
+                next_screen = get_thread(hit.thread_url())?.map(|story| {
+                    let mut view = View::thread(hit.thread_url().clone(), story);
+                    view.focus_comment(hit.comment().short_id());
+                    Screen::Stories(Box::new(view))
+                });
+            }
+        }
+        // Open the thread in the browser.
+        Key::Char('o') => {
+            if let Some(hit) = hits.get_selected_hit() {
+                webbrowser::open(hit.thread_url())?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(next_screen)
 }
 
-fn get_stories(page: u16) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
-    let url = format!("{PAGE_URL}/{page}");
-    let html = get_page(url)?;
-    let stories_selector = Selector::parse("ol.stories > .story > .story_liner").unwrap();
-    let stories_list = html.select(&stories_selector);
-    Ok(stories_list.map(Story::from_html).collect())
+/// Handles a key press on the messages view.
+fn handle_messages_key(
+    term: &Term,
+    frame: &mut FrameBuffer,
+    messages: &mut MessagesView,
+    input: Key,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match input {
+        Key::Char('J') => messages.go_to(Travel::NextStep),
+        Key::Char('K') => messages.go_to(Travel::PrevStep),
+        Key::Char('j') | Key::ArrowDown => messages.go_to(Travel::NextItem),
+        Key::Char('k') | Key::ArrowUp => messages.go_to(Travel::PrevItem),
+        Key::Char('g') => messages.go_to(Travel::Top),
+        Key::Char('G') => messages.go_to(Travel::Bottom),
+        Key::Char('l') | Key::ArrowRight | Key::Enter => messages.open(),
+        Key::Char('h') | Key::ArrowLeft => messages.close(),
+        // Switch between the inbox and the sent messages.
+        Key::Char('t') => messages.toggle_folder(),
+        // Reply to the selected message.
+        Key::Char('R') => {
+            let context = messages.reply_context().ok_or("no message selected")?;
+            frame.invalidate();
+            if let Some(text) = frame::suspended(term, || editor::write(&context))?? {
+                messages.reply(&text)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Runs the program until the user quits.
+///
+/// The terminal is in the alternative screen buffer for as long as this runs, and is restored
+/// before it returns, also when it returns an error.
+fn run(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     let mut term = Term::stdout();
     term.set_title("kreeftje");
-    term.hide_cursor()?;
-    print!("\u{1b}[?1049h"); // Open the alternative screen buffer.
-    term.clear_screen()?;
+    let _screen = AlternateScreen::enter(&term)?;
+
+    let mut screens = vec![Screen::Stories(Box::new(View::new()))];
+    let mut frame = FrameBuffer::new();
+    let mut size = term.size();
+
+    let mut last_error: Option<String> = None;
 
-    let (rows, columns) = console::Term::stdout().size();
-    let mut view = View::new(rows as usize / 3);
+    // Logging in is not required, so failing to do so is not fatal either.
+    let session = Session::load().unwrap_or_else(|err| {
+        last_error = Some(format!("cannot log in: {err}"));
+        None
+    });
+    // While the replies are polled, the screen is drawn again when their number changes, rather
+    // than only after the next key press.
+    let mut keys = KeyReader::new(&term);
+    let redraw_interval = if session.is_some() && config.session.poll_replies_secs > 0 {
+        session::poll_replies(Duration::from_secs(config.session.poll_replies_secs));
+        Some(REDRAW_INTERVAL)
+    } else {
+        None
+    };
 
     'listen: loop {
-        view.load_stories_next_page()?;
-        if let ViewMode::Comments = view.mode() {
-            let story = view.get_selected_story_mut();
-
-            if story.comments_descendants() != story.comments_number() {
-                story.load_comments()?
-            }
-        }
-        term.move_cursor_to(0, 0)?;
-        term.clear_line()?;
-
-        let view_string = view.generate_string(columns, rows);
-        #[cfg(debug_assertions)]
-        term.write_fmt(format_args!(
-            "page {} (list_pos: {}, comments_pos: {}, stored comments count: {})\n",
-            view.site_page(),
-            view.pos(),
-            view.comments_pos,
-            view.get_selected_story().comments_descendants()
-        ))?;
-        #[cfg(not(debug_assertions))]
-        term.write_fmt(format_args!("page {}\n", view.site_page()))?;
-        term.write_all(view_string.as_bytes())?;
-        let input = term.read_key()?;
-
-        let prev_site_page = view.view_page();
-        let prev_view_mode = view.mode();
+        // Query the size on every frame, such that the layout follows when the terminal is
+        // resized.
+        let (term_rows, term_columns) = term.size();
+        if (term_rows, term_columns) != size {
+            size = (term_rows, term_columns);
+            term.clear_screen()?;
+            frame.invalidate();
+        }
+        let (rows, columns) = (term_rows.max(MIN_ROWS), term_columns.max(MIN_COLUMNS));
+        // The status bar takes up the top line, the screen is drawn below it.
+        let body_rows = rows - 1;
 
-        match input {
-            // J — vv
-            // Load next page.
-            Key::Char('J') => view.go_to(Travel::NextStep),
-            // K — ^^
-            // Load previous page.
-            Key::Char('K') => view.go_to(Travel::PrevStep),
-            // j — v
-            // Select next story.
-            Key::Char('j') | Key::ArrowDown => view.go_to(Travel::NextItem),
-            // k — ^
-            // Select other story.
-            Key::Char('k') | Key::ArrowUp => view.go_to(Travel::PrevItem),
-            // l — >
-            // Open comments.
-            Key::Char('l') | Key::ArrowRight => view.view_comments(),
-            // h — <
-            // Close comments.
-            Key::Char('h') | Key::ArrowLeft => view.view_list(),
-            // Toggle comments.
-            Key::Char('c') | Key::Tab => view.view_toggle(),
-            // g — ^^
-            // Go to first page.
-            Key::Char('g') => view.go_to(Travel::Top),
-            // G — vv
-            // Go to last loaded page.
-            Key::Char('G') => view.go_to(Travel::Bottom),
-            // Open link in browser.
-            Key::Char('o') | Key::Enter => webbrowser::open(view.get_selected_story().url())?,
+        let (body, mut status) = match screens.last_mut().unwrap() {
+            Screen::Stories(view) => render_stories(view, columns, body_rows),
+            Screen::Hits(hits) => render_hits(hits, columns, body_rows),
+            Screen::Messages(messages) => {
+                messages.load();
+                (
+                    messages.generate_string(columns, body_rows),
+                    messages.status(),
+                )
+            }
+            Screen::Profile(profile) => {
+                let header = profile.header(columns);
+                let body_rows = body_rows.saturating_sub(header.lines().count() as u16 + 1);
+                let (body, status) = match profile.tab() {
+                    ProfileTab::Stories => {
+                        render_stories(profile.stories_mut(), columns, body_rows)
+                    }
+                    ProfileTab::Comments => render_hits(profile.comments_mut(), columns, body_rows),
+                };
+                (format!("{header}\n\n{body}"), status)
+            }
+        };
+        if let Some(session) = &session {
+            status.user = Some(session.user().to_string());
+            status.unread_replies = session::unread_replies();
+            if let Screen::Stories(view) = screens.last().unwrap() {
+                if let (Source::Lobsters, Listing::Front) = (view.source(), view.listing()) {
+                    status.filtered_tags = session.filtered_tags().to_vec();
+                }
+            }
+        }
+        // An error from the last key press takes precedence over an error from loading.
+        if let Some(error) = last_error.take() {
+            status.error = Some(error);
+        }
+        let status_line = status.to_string(columns as usize);
+        frame.draw(
+            &mut term,
+            &format!("{status_line}\n{body}"),
+            term_columns,
+            term_rows,
+        )?;
+
+        // Actions on the site are shown before they are sent, so they are sent after drawing.
+        match screens.last_mut().unwrap().perform_pending() {
+            Ok(false) => {}
+            Ok(true) => continue 'listen,
+            Err(err) => {
+                last_error = Some(err.to_string());
+                continue 'listen;
+            }
+        }
+
+        let input = loop {
+            if let Some(key) = keys.read_key(redraw_interval)? {
+                break key;
+            }
+            if session::unread_replies() != status.unread_replies {
+                continue 'listen;
+            }
+        };
 
+        // Keys that work the same on every screen.
+        match input {
             // Quit
             Key::Char('q') => {
                 // q to quit (<ctrl-C> als works, of course)
                 break 'listen;
             }
-
-            // Otherwise, do nothing
+            // Show the key bindings until the next key press.
+            Key::Char('?') => {
+                draw_help(&mut term, term_columns, term_rows)?;
+                frame.invalidate();
+                term.read_key()?;
+                continue 'listen;
+            }
+            // Try loading again after an error.
+            Key::Char('r') => {
+                screens.last_mut().unwrap().retry();
+                continue 'listen;
+            }
+            // Go back to the previous screen.
+            Key::Backspace | Key::Escape if screens.len() > 1 => {
+                screens.pop();
+                continue 'listen;
+            }
+            // Search the site for stories or comments.
+            Key::Char('s') | Key::Char('S') => {
+                let what = if input == Key::Char('s') {
+                    What::Stories
+                } else {
+                    What::Comments
+                };
+                let prompt = match what {
+                    What::Stories => "search stories: ",
+                    What::Comments => "search comments: ",
+                };
+                let input = read_prompt(&mut term, &mut frame, prompt)?;
+                if let Some(search) = SiteSearch::parse(&input, what) {
+                    screens.push(match search.what() {
+                        What::Stories => {
+                            Screen::Stories(Box::new(View::with_listing(Listing::Search(search))))
+                        }
+                        What::Comments => Screen::Hits(HitsView::new(Listing::Search(search))),
+                    });
+                }
+                continue 'listen;
+            }
+            // Open the stories the user has hidden, the stories or comments they upvoted, the
+            // replies to them, or their messages.
+            Key::Char('H') | Key::Char('v') | Key::Char('V') | Key::Char('i') | Key::Char('m') => {
+                if session.is_none() {
+                    last_error = Some(NOT_LOGGED_IN.to_string());
+                    continue 'listen;
+                }
+                screens.push(match input {
+                    Key::Char('H') => {
+                        Screen::Stories(Box::new(View::with_listing(Listing::Hidden)))
+                    }
+                    Key::Char('v') => Screen::Stories(Box::new(View::with_listing(
+                        Listing::Upvoted(What::Stories),
+                    ))),
+                    Key::Char('V') => Screen::Hits(HitsView::new(Listing::Upvoted(What::Comments))),
+                    Key::Char('i') => Screen::Hits(HitsView::new(Listing::Replies)),
+                    _ => Screen::Messages(MessagesView::new()),
+                });
+                continue 'listen;
+            }
+            // Open the profile of the selected user.
+            Key::Char('u') => {
+                if let Some(user) = screens.last().unwrap().selected_user() {
+                    match ProfileView::load(user) {
+                        Ok(profile) => screens.push(Screen::Profile(Box::new(profile))),
+                        Err(err) => last_error = Some(err.to_string()),
+                    }
+                }
+                continue 'listen;
+            }
             _ => {}
         }
 
-        if view.mode() == ViewMode::Comments
-            || prev_view_mode != view.mode()
-            || prev_site_page != view.view_page()
-        {
-            term.clear_screen()?;
+        let next_screen = match screens.last_mut().unwrap() {
+            Screen::Stories(view) => {
+                handle_stories_key(&mut term, &mut frame, view, input).map(|_| None)
+            }
+            Screen::Hits(hits) => handle_hits_key(hits, input),
+            Screen::Profile(profile) => match input {
+                // Switch between the stories and comments of the user.
+                Key::Char('t') => {
+                    profile.toggle_tab();
+                    Ok(None)
+                }
+                _ => match profile.tab() {
+                    ProfileTab::Stories => {
+                        handle_stories_key(&mut term, &mut frame, profile.stories_mut(), input)
+                            .map(|_| None)
+                    }
+                    ProfileTab::Comments => handle_hits_key(profile.comments_mut(), input),
+                },
+            },
+            Screen::Messages(messages) => {
+                handle_messages_key(&term, &mut frame, messages, input).map(|_| None)
+            }
+        };
+        // Failing to handle a key press is not fatal, the error is shown in the status bar.
+        let next_screen = next_screen.unwrap_or_else(|err| {
+            last_error = Some(err.to_string());
+            None
+        });
+        if let Some(screen) = next_screen {
+            screens.push(screen);
         }
-        term.move_cursor_to(0, 0)?;
-        term.clear_line()?;
     }
 
-    term.clear_screen()?;
-    term.show_cursor()?;
-    print!("\u{1b}[?1049l"); // Close the alternative screen buffer again.
-
     Ok(())
 }
+
+/// Loads the configuration, applies the options that come before the command in `args` to it, and
+/// sets up everything that depends on it. The options are removed from `args`.
+fn setup(args: &mut Vec<String>) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut config = Config::load()?;
+    while let Some(option) = args.first() {
+        match option.as_str() {
+            "--source" => {
+                let source = args.get(1).ok_or("--source needs a value")?;
+                config.site.source = Source::from_str(source)?;
+                args.drain(..2);
+            }
+            option if option.starts_with("--") => {
+                return Err(format!("unknown option {option}").into())
+            }
+            _ => break,
+        }
+    }
+    settings::init(&config)?;
+    Ok(config)
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let result = setup(&mut args).and_then(|config| match args.first().map(String::as_str) {
+        Some("submit") => submit::run(&args[1..]),
+        Some(command) => Err(format!("unknown command {command:?}").into()),
+        None => run(&config),
+    });
+    if let Err(err) = result {
+        eprintln!("kreeftje: {err}");
+        let mut source = err.source();
+        while let Some(err) = source {
+            eprintln!("  caused by: {err}");
+            source = err.source();
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,207 @@
+//! The merged front page, which lists the stories of several sources together.
+
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::config::MergedConfig;
+use crate::listing::Listing;
+use crate::story::Story;
+
+/// Query parameters that only track where a visitor came from, and do not change the page.
+const TRACKING_PARAMETERS: &[&str] = &["fbclid", "gclid", "mc_cid", "mc_eid", "ref_src"];
+
+/// The order of the stories on the merged front page.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Ranking {
+    /// The sources take turns: first the top story of every source, then the second, and so on.
+    #[default]
+    Interleave,
+    /// The highest score first. Stories from sources without scores come last.
+    Score,
+    /// The newest first.
+    Newest,
+}
+
+/// Gets a page of the merged front page as set in `config`, which holds that page of the front
+/// page of every merged source.
+///
+/// Stories that link to the same page are listed once, with the others as its other discussions.
+/// This only happens within a page: a story that shows up on different pages of two sources is
+/// listed twice.
+pub(crate) fn get_stories(
+    config: &MergedConfig,
+    page: usize,
+) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+    let mut entries: Vec<((u64, usize, usize), Story)> = Vec::new();
+    let mut by_url: HashMap<String, usize> = HashMap::new();
+    for (source_idx, source) in config.sources.iter().enumerate() {
+        let stories = source
+            .get_stories(&Listing::Front, page)
+            .map_err(|err| format!("{source}: {err}"))?;
+        for (pos, story) in stories.into_iter().enumerate() {
+            let url = normalize_url(story.url());
+            if let Some(&idx) = by_url.get(&url) {
+                entries[idx].1.add_discussion(story);
+                continue;
+            }
+            let rank = match config.ranking {
+                Ranking::Interleave => pos as u64,
+                Ranking::Score => story
+                    .votes()
+                    .map_or(u64::MAX, |votes| u64::MAX - 1 - votes as u64),
+                Ranking::Newest => story.age_minutes().unwrap_or(u64::MAX),
+            };
+            by_url.insert(url, entries.len());
+            entries.push(((rank, pos, source_idx), story.merged()));
+        }
+    }
+    entries.sort_by_key(|(rank, _)| *rank);
+    Ok(entries.into_iter().map(|(_, story)| story).collect())
+}
+
+/// Reduces `url` to what identifies the page it links to, such that links to the same page that
+/// are written differently are equal.
+///
+/// The scheme, a leading `www.`, a trailing slash, the fragment and tracking parameters are left
+/// out, and the host is lowercased.
+pub(crate) fn normalize_url(url: &str) -> String {
+    let url = match Url::parse(url.trim()) {
+        Ok(url) => url,
+        Err(_) => return url.trim().to_lowercase(),
+    };
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let port = url
+        .port()
+        .map(|port| format!(":{port}"))
+        .unwrap_or_default();
+    let path = url.path().trim_end_matches('/');
+    let query = url
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking_parameter(key))
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<String>>();
+    if query.is_empty() {
+        format!("{host}{port}{path}")
+    } else {
+        format!("{host}{port}{path}?{}", query.join("&"))
+    }
+}
+
+fn is_tracking_parameter(key: &str) -> bool {
+    key.starts_with("utm_") || TRACKING_PARAMETERS.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Source;
+    use crate::testing::{fixture, stand_in};
+
+    #[test]
+    fn urls_are_normalized() {
+        let normalized = normalize_url("https://www.example.com/posts/borrowing");
+        assert_eq!(normalized, "example.com/posts/borrowing");
+        for url in [
+            "http://example.com/posts/borrowing",
+            "https://WWW.Example.COM/posts/borrowing/",
+            "https://example.com/posts/borrowing#comments",
+            "https://example.com/posts/borrowing?utm_source=rss&utm_medium=feed",
+            "https://example.com/posts/borrowing?fbclid=abc",
+            " https://example.com/posts/borrowing ",
+        ] {
+            assert_eq!(normalize_url(url), normalized, "{url}");
+        }
+        // The path is not lowercased, and ports and other query parameters are kept.
+        assert_eq!(
+            normalize_url("https://example.com/Posts"),
+            "example.com/Posts"
+        );
+        assert_eq!(
+            normalize_url("https://example.com:8080/posts/"),
+            "example.com:8080/posts"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/item?id=1&utm_campaign=x&page=2"),
+            "example.com/item?id=1&page=2"
+        );
+        assert_eq!(normalize_url("Not a URL"), "not a url");
+    }
+
+    /// The config of a merged front page of a feed and another site, served by the stand-in.
+    fn config(ranking: Ranking) -> MergedConfig {
+        // The paths are only used by this test, as the stand-in is shared by all tests.
+        stand_in().serve_page("/merged/site/page/1", &fixture("search_stories.html"));
+        stand_in().serve(
+            "/merged/feed.xml",
+            200,
+            "application/rss+xml",
+            r#"<rss version="2.0"><channel>
+                <item>
+                  <title>Borrowing without tears</title>
+                  <link>http://Example.com/posts/borrowing/?utm_source=rss#comments</link>
+                </item>
+                <item>
+                  <title>Old news</title>
+                  <link>https://example.org/old</link>
+                  <pubDate>Mon, 01 Jan 2001 00:00:00 +0000</pubDate>
+                </item>
+              </channel></rss>"#,
+        );
+        MergedConfig {
+            sources: vec![
+                Source::Feed(stand_in().url("/merged/feed.xml")),
+                Source::Instance(stand_in().url("/merged/site")),
+            ],
+            ranking,
+        }
+    }
+
+    fn titles(stories: &[Story]) -> Vec<&str> {
+        stories.iter().map(Story::title).collect()
+    }
+
+    #[test]
+    fn stories_on_both_sources_are_listed_once() {
+        let stories = get_stories(&config(Ranking::Interleave), 1).unwrap();
+        assert_eq!(
+            titles(&stories),
+            [
+                "Borrowing without tears",
+                "Old news",
+                "What are you doing in Rust this week?"
+            ]
+        );
+        // The story is listed under the first source, with the other as its other discussion.
+        let story = &stories[0];
+        assert!(matches!(story.source(), Source::Feed(_)));
+        assert_eq!(story.others().len(), 1);
+        assert!(matches!(story.others()[0].source(), Source::Instance(_)));
+        assert_eq!(story.others()[0].votes(), Some(25));
+    }
+
+    #[test]
+    fn stories_ranked_by_score_or_age() {
+        // Stories without a score or time come last.
+        let stories = get_stories(&config(Ranking::Score), 1).unwrap();
+        assert_eq!(
+            titles(&stories),
+            [
+                "What are you doing in Rust this week?",
+                "Borrowing without tears",
+                "Old news"
+            ]
+        );
+        let stories = get_stories(&config(Ranking::Newest), 1).unwrap();
+        assert_eq!(
+            titles(&stories),
+            [
+                "What are you doing in Rust this week?",
+                "Old news",
+                "Borrowing without tears"
+            ]
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use console::style;
+use scraper::{ElementRef, Html, Selector};
+use std::time::Instant;
+
+use crate::http::{base_url, get_page, post_form};
+use crate::pages::{Layout, Pages};
+use crate::status::Status;
+use crate::story::prepend_string;
+use crate::view::Travel;
+use crate::wrap::wrap;
+
+/// The folders of private messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Folder {
+    Inbox,
+    Sent,
+}
+
+impl Folder {
+    fn url(&self) -> String {
+        match self {
+            Folder::Inbox => format!("{}/messages", base_url()),
+            Folder::Sent => format!("{}/messages/sent", base_url()),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Folder::Inbox => "inbox",
+            Folder::Sent => "sent messages",
+        }
+    }
+}
+
+/// A private message in the list of a folder.
+#[derive(Debug, Clone)]
+pub(crate) struct Message {
+    short_id: String,
+    subject: String,
+    /// The sender of a received message, or the recipient of a sent one.
+    user: String,
+    time: String,
+    unread: bool,
+}
+
+impl Message {
+    /// Parses a message from a row of the table of messages. Returns `None` for rows that are not
+    /// a message, such as the header.
+    fn from_html(row: ElementRef) -> Option<Self> {
+        let link = |prefix: &str| {
+            row.select(&Selector::parse("a").unwrap())
+                .find(|a| a.value().attr("href").unwrap_or("").starts_with(prefix))
+        };
+        let subject = link("/messages/")?;
+        let short_id = subject.value().attr("href")?.strip_prefix("/messages/")?;
+        let user = link("/~").map(|a| a.text().collect::<String>());
+        let time = row
+            .select(&Selector::parse("td").unwrap())
+            .filter(|td| {
+                td.select(&Selector::parse("a, input").unwrap())
+                    .next()
+                    .is_none()
+            })
+            .map(|td| td.text().collect::<String>().trim().to_string())
+            .find(|text| !text.is_empty());
+
+        Some(Self {
+            short_id: short_id.to_string(),
+            subject: subject.text().collect::<String>().trim().to_string(),
+            user: user.unwrap_or_default().trim().to_string(),
+            time: time.unwrap_or_default(),
+            // The site shows unread messages in bold.
+            unread: row
+                .value()
+                .classes()
+                .any(|class| class == "unread" || class == "bold"),
+        })
+    }
+
+    fn to_string(&self, width: usize, selected: bool) -> String {
+        let marker = if self.unread { "new" } else { "" };
+        let line = format!(
+            "{marker:<3}  {user:<16} {time:<16} {subject}",
+            user = self.user,
+            time = self.time,
+            subject = self.subject
+        );
+        let line = console::truncate_str(&line, width, "…").into_owned();
+        match (selected, self.unread) {
+            (true, _) => style(line).reverse().to_string(),
+            (false, true) => style(line).bold().to_string(),
+            (false, false) => line,
+        }
+    }
+}
+
+/// Gets the list of messages in a folder.
+fn get_messages(folder: Folder) -> Result<Vec<Message>, Box<dyn std::error::Error>> {
+    let html = get_page(folder.url())?;
+    let rows = Selector::parse("table tr").unwrap();
+    Ok(html.select(&rows).filter_map(Message::from_html).collect())
+}
+
+/// Gets the text of a message from its page.
+fn get_message_body(short_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let html = get_page(format!("{}/messages/{short_id}", base_url()))?;
+    Ok(body_from_html(&html))
+}
+
+fn body_from_html(html: &Html) -> String {
+    let selector = Selector::parse(".comment_text").unwrap();
+    html.select(&selector)
+        .next()
+        .map(|body| body.text().collect::<String>().trim().to_string())
+        .unwrap_or_default()
+}
+
+/// A view of the private messages of the logged in user, with the text of the opened message
+/// in a pane below the list.
+pub(crate) struct MessagesView {
+    folder: Folder,
+    messages: Vec<Message>,
+    loaded: bool,
+    /// The error that occurred when loading, until a retry is requested.
+    error: Option<String>,
+    loaded_at: Option<Instant>,
+    /// The selected message and the presented page of the list.
+    pages: Pages,
+    /// The number of rows the list is drawn in.
+    list_height: usize,
+    /// Whether the message pane is open.
+    open: bool,
+    /// The short id and text of the message in the pane, once it is loaded.
+    body: Option<(String, String)>,
+    /// The scroll position of the message pane.
+    body_pos: usize,
+    /// The scroll position at which the last line of the message pane, as it was last rendered, is
+    /// at the bottom.
+    body_end: usize,
+}
+
+impl MessagesView {
+    pub(crate) fn new() -> Self {
+        Self {
+            folder: Folder::Inbox,
+            messages: Vec::new(),
+            loaded: false,
+            error: None,
+            loaded_at: None,
+            pages: Pages::default(),
+            list_height: 1,
+            open: false,
+            body: None,
+            body_pos: 0,
+            body_end: 0,
+        }
+    }
+
+    /// Loads the messages of the folder and the text of the opened message, if they are not
+    /// loaded yet. If that fails, the error is kept and shown in the status bar, and no further
+    /// loading is attempted until [`MessagesView::retry`] is called.
+    pub(crate) fn load(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(err) = self.load_messages() {
+            self.error = Some(err.to_string());
+        }
+    }
+
+    fn load_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.loaded {
+            self.messages = get_messages(self.folder)?;
+            self.loaded = true;
+            self.loaded_at = Some(Instant::now());
+            self.pages.pos = self.pages.pos.min(self.messages.len().saturating_sub(1));
+        }
+        if !self.open {
+            return Ok(());
+        }
+        let message = match self.messages.get_mut(self.pages.pos) {
+            Some(message) => message,
+            None => return Ok(()),
+        };
+        if self.body.as_ref().map(|(id, _)| id) != Some(&message.short_id) {
+            let body = get_message_body(&message.short_id)?;
+            // Opening a message on the site marks it as read.
+            message.unread = false;
+            self.body = Some((message.short_id.clone(), body));
+            self.body_pos = 0;
+        }
+        Ok(())
+    }
+
+    /// Allows loading to be attempted again after an error.
+    pub(crate) fn retry(&mut self) {
+        self.error = None;
+    }
+
+    /// Switches between the inbox and the sent messages.
+    pub(crate) fn toggle_folder(&mut self) {
+        self.folder = match self.folder {
+            Folder::Inbox => Folder::Sent,
+            Folder::Sent => Folder::Inbox,
+        };
+        self.reload();
+    }
+
+    /// Loads the messages again on the next frame.
+    fn reload(&mut self) {
+        self.messages.clear();
+        self.loaded = false;
+        self.pages = Pages::default();
+        self.open = false;
+    }
+
+    /// Opens the selected message in the message pane.
+    pub(crate) fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Closes the message pane.
+    pub(crate) fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Moves through the list, or scrolls the message pane when it is open.
+    pub(crate) fn go_to(&mut self, travel: Travel) {
+        if self.open {
+            match travel {
+                Travel::NextItem => self.body_pos += 1,
+                Travel::PrevItem => self.body_pos -= self.body_pos.min(1),
+                Travel::Top => self.body_pos = 0,
+                Travel::Bottom => self.body_pos = self.body_end,
+                // Moving by a step goes to the next or previous message, like moving to the next
+                // story does in the comments view.
+                Travel::NextStep => self.pages.pos += 1,
+                Travel::PrevStep => self.pages.pos -= self.pages.pos.min(1),
+            }
+        } else {
+            self.pages.go_to(travel, &self.layout());
+        }
+        self.pages.pos = self.pages.pos.min(self.messages.len().saturating_sub(1));
+    }
+
+    /// How the messages take up the lines of the list, one line each.
+    fn layout(&self) -> Layout<'static> {
+        Layout::lines(self.messages.len(), self.list_height)
+    }
+
+    fn get_selected_message(&self) -> Option<&Message> {
+        self.messages.get(self.pages.pos)
+    }
+
+    /// Returns the other user of the selected message.
+    pub(crate) fn selected_user(&self) -> Option<&str> {
+        let user = &self.get_selected_message()?.user;
+        (!user.is_empty()).then_some(user.as_str())
+    }
+
+    /// Returns the text the editor starts out with for a reply to the selected message.
+    pub(crate) fn reply_context(&self) -> Option<String> {
+        let message = self.get_selected_message()?;
+        let mut context = format!("Replying to {}: {}", message.user, message.subject);
+        if let Some((id, body)) = &self.body {
+            if *id == message.short_id {
+                context.push_str("\n\n");
+                context.push_str(body);
+            }
+        }
+        Some(context)
+    }
+
+    /// Sends `text` to the other user of the selected message, with the same subject.
+    pub(crate) fn reply(&mut self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let message = self.get_selected_message().ok_or("no message selected")?;
+        let subject = if message.subject.starts_with("Re: ") {
+            message.subject.clone()
+        } else {
+            format!("Re: {}", message.subject)
+        };
+        post_form(
+            format!("{}/messages", base_url()),
+            &[
+                ("message[recipient_username]", message.user.as_str()),
+                ("message[subject]", subject.as_str()),
+                ("message[body]", text),
+            ],
+        )?;
+        // The reply shows up among the sent messages.
+        if self.folder == Folder::Sent {
+            self.reload();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn generate_string(&mut self, width: u16, height: u16) -> String {
+        if self.messages.is_empty() {
+            return "No messages.".to_string();
+        }
+        let margin = 2;
+        let width = (width as usize).saturating_sub(margin * 2);
+        let height = height as usize;
+        // With a message open, the list takes up a third of the screen, and the message the rest.
+        self.list_height = if self.open {
+            (height / 3).max(1)
+        } else {
+            height.max(1)
+        };
+        let layout = self.layout();
+        self.pages.fit(&layout);
+        let mut lines: Vec<String> = self
+            .pages
+            .page(&layout)
+            .map(|pos| self.messages[pos].to_string(width, pos == self.pages.pos))
+            .collect();
+
+        if self.open {
+            lines.resize(self.list_height, String::new());
+            lines.push(style("─".repeat(width)).dim().to_string());
+            let pane_height = height.saturating_sub(self.list_height + 1);
+            let mut pane = Vec::new();
+            if let Some(message) = self.get_selected_message() {
+                pane.push(style(&message.subject).bold().to_string());
+                pane.push(
+                    style(format!("{} {}", message.user, message.time))
+                        .dim()
+                        .to_string(),
+                );
+                pane.push(String::new());
+                if let Some((_, body)) = &self.body {
+                    pane.extend(wrap(body, width));
+                }
+            }
+            self.body_end = pane.len().saturating_sub(pane_height);
+            self.body_pos = self.body_pos.min(self.body_end);
+            lines.extend(pane.into_iter().skip(self.body_pos).take(pane_height));
+        }
+
+        prepend_string(&lines.join("\n"), &" ".repeat(margin))
+    }
+
+    /// Returns the contents of the status bar for this view.
+    pub(crate) fn status(&self) -> Status {
+        Status {
+            position: Some((self.pages.pos, self.messages.len())),
+            loaded_at: self.loaded_at,
+            error: self.error.as_ref().map(|err| format!("{err} (r to retry)")),
+            ..Status::new(self.folder.as_str().to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fixture, stand_in};
+
+    fn inbox() -> MessagesView {
+        stand_in().serve_page("/messages", &fixture("messages_inbox.html"));
+        stand_in().serve_page("/messages/m1abcd", &fixture("message.html"));
+        let mut messages = MessagesView::new();
+        messages.load();
+        assert_eq!(messages.error, None);
+        messages
+    }
+
+    #[test]
+    fn messages_from_the_inbox() {
+        let messages = inbox();
+        let subjects = messages
+            .messages
+            .iter()
+            .map(|message| message.subject.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            subjects,
+            [
+                "Hello there",
+                "Re: Your story about parsers",
+                "Invitation",
+                "Moderation question"
+            ]
+        );
+        let message = &messages.messages[0];
+        assert_eq!(message.short_id, "m1abcd");
+        assert_eq!(message.user, "dave");
+        assert_eq!(message.time, "3 hours ago");
+        assert!(message.unread);
+        assert!(!messages.messages[1].unread);
+    }
+
+    #[test]
+    fn opening_a_message_reads_it() {
+        let mut messages = inbox();
+        messages.open();
+        messages.load();
+        assert!(!messages.messages[0].unread);
+        let text = console::strip_ansi_codes(&messages.generate_string(100, 12)).into_owned();
+        assert!(text.contains("Hello there"));
+        assert!(text.contains("Hi alice, thanks for the story on parsers."));
+        assert!(messages
+            .reply_context()
+            .unwrap()
+            .starts_with("Replying to dave: Hello there\n\nHi alice"));
+    }
+
+    #[test]
+    fn list_is_shown_a_page_at_a_time() {
+        let mut messages = inbox();
+        let shown = |messages: &mut MessagesView| {
+            let text = messages.generate_string(100, 2);
+            console::strip_ansi_codes(&text)
+                .lines()
+                .filter_map(|line| {
+                    let users = ["dave", "erin", "frank", "grace"];
+                    users.into_iter().find(|user| line.contains(user))
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shown(&mut messages), ["dave", "erin"]);
+        messages.go_to(Travel::NextItem);
+        messages.go_to(Travel::NextItem);
+        assert_eq!(shown(&mut messages), ["frank", "grace"]);
+        assert_eq!(messages.selected_user(), Some("frank"));
+        messages.go_to(Travel::PrevStep);
+        assert_eq!(shown(&mut messages), ["dave", "erin"]);
+        messages.go_to(Travel::Bottom);
+        assert_eq!(messages.selected_user(), Some("grace"));
+    }
+
+    #[test]
+    fn reply_keeps_the_subject() {
+        let mut messages = inbox();
+        messages.go_to(Travel::NextItem);
+        messages.reply("Sure, go ahead.").unwrap();
+        let posts = stand_in()
+            .requests_to("/messages")
+            .into_iter()
+            .filter(|request| request.method == "POST")
+            .collect::<Vec<_>>();
+        assert_eq!(posts.len(), 1);
+        let body = &posts[0].body;
+        assert!(body.contains("message%5Brecipient_username%5D=erin"));
+        assert!(body.contains("message%5Bsubject%5D=Re%3A+Your+story+about+parsers"));
+        assert!(body.contains("message%5Bbody%5D=Sure%2C+go+ahead."));
+    }
+}
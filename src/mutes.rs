@@ -0,0 +1,158 @@
+//! Mute lists, of domains and users whose stories and comments are collapsed to a single line.
+//!
+//! The lists are kept in `mutes.toml`, next to the configuration file, and are changed from within
+//! the program. Stories with a mute word in the title are hidden instead, see [`crate::words`].
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::config::Config;
+use crate::settings::settings;
+use crate::story::{Comment, Story};
+
+/// The mute lists, together with the file they are kept in.
+#[derive(Debug, Default)]
+pub(crate) struct Mutes {
+    /// The file the lists are saved to, if there is a place for it.
+    path: Option<PathBuf>,
+    lists: Mutex<MuteLists>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MuteLists {
+    /// Muted domains, which also mute their subdomains.
+    domains: Vec<String>,
+    /// Muted users, as submitters of stories and authors of comments.
+    users: Vec<String>,
+}
+
+/// Returns the path of the file the mute lists are kept in, next to the configuration file.
+pub(crate) fn path() -> Option<PathBuf> {
+    Some(Config::path()?.with_file_name("mutes.toml"))
+}
+
+impl Mutes {
+    /// Reads the mute lists from the file at `path`. A missing file results in empty lists.
+    pub(crate) fn load(path: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error>> {
+        let lists = match &path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(path)?;
+                let mut lists: MuteLists = toml::from_str(&contents)
+                    .map_err(|err| format!("invalid mute lists in {}: {err}", path.display()))?;
+                // The file may have been edited by hand.
+                lists.domains = lists.domains.iter().map(|d| domain_entry(d)).collect();
+                lists.users = lists.users.iter().map(|u| user_entry(u)).collect();
+                lists
+            }
+            _ => MuteLists::default(),
+        };
+        Ok(Self {
+            path,
+            lists: Mutex::new(lists),
+        })
+    }
+
+    /// Writes the mute lists to their file.
+    fn save(&self, lists: &MuteLists) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("there is no configuration directory to keep the mute lists in")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string(lists)?)?;
+        Ok(())
+    }
+}
+
+fn lists() -> MutexGuard<'static, MuteLists> {
+    settings().mutes.lists.lock().unwrap()
+}
+
+/// Returns `domain` the way it is kept in the mute list: in lowercase and without `www.`.
+fn domain_entry(domain: &str) -> String {
+    domain.to_lowercase().trim_start_matches("www.").to_string()
+}
+
+/// Returns `user` the way it is kept in the mute list: in lowercase and without `~`.
+fn user_entry(user: &str) -> String {
+    user.trim_start_matches('~').to_lowercase()
+}
+
+/// Adds `entry` to `list`, or removes it when it is already there, and saves the lists.
+fn toggle(
+    entry: String,
+    list: fn(&mut MuteLists) -> &mut Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut lists = lists();
+    let entries = list(&mut lists);
+    match entries.iter().position(|e| *e == entry) {
+        Some(idx) => {
+            entries.remove(idx);
+        }
+        None => entries.push(entry),
+    }
+    settings().mutes.save(&lists)
+}
+
+/// Mutes `domain`, or unmutes it when it is muted.
+pub(crate) fn toggle_domain(domain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    toggle(domain_entry(domain), |lists| &mut lists.domains)
+}
+
+/// Mutes `user`, or unmutes them when they are muted.
+pub(crate) fn toggle_user(user: &str) -> Result<(), Box<dyn std::error::Error>> {
+    toggle(user_entry(user), |lists| &mut lists.users)
+}
+
+/// Returns the muted domain that `domain` is on, which may be `domain` itself.
+fn muted_domain(domain: &str) -> Option<String> {
+    let domain = domain_entry(domain);
+    lists()
+        .domains
+        .iter()
+        .find(|muted| domain == **muted || domain.ends_with(&format!(".{muted}")))
+        .cloned()
+}
+
+fn is_muted_user(user: &str) -> bool {
+    let user = user_entry(user);
+    lists().users.contains(&user)
+}
+
+/// Returns why `story` is muted, such as "from example.com", or `None` if it is not.
+pub(crate) fn story_muted(story: &Story) -> Option<String> {
+    if let Some(domain) = story.domain().and_then(muted_domain) {
+        return Some(format!("from {domain}"));
+    }
+    story
+        .byline()
+        .user()
+        .filter(|user| is_muted_user(user))
+        .map(|user| format!("via {user}"))
+}
+
+/// Returns whether `comment` is by a muted user.
+pub(crate) fn comment_muted(comment: &Comment) -> bool {
+    is_muted_user(comment.author())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_read_in_lowercase() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let contents =
+            "domains = [\"Example.com\", \"www.Blog.example.org\"]\nusers = [\"~Alice\"]\n";
+        std::io::Write::write_all(&mut file, contents.as_bytes()).unwrap();
+        let mutes = Mutes::load(Some(file.path().to_path_buf())).unwrap();
+        let lists = mutes.lists.lock().unwrap();
+        assert_eq!(lists.domains, ["example.com", "blog.example.org"]);
+        assert_eq!(lists.users, ["alice"]);
+    }
+}
@@ -0,0 +1,165 @@
+//! The selection in a list that is shown a page at a time, such as the stories of a listing or the
+//! messages in a folder.
+//!
+//! A page holds as many entries as fit in its height, where entries may take up different numbers
+//! of lines. Moving the selection off the page moves the page along, such that the selection stays
+//! on the page that is shown.
+
+use std::ops::Range;
+
+use crate::view::Travel;
+
+/// How the entries of a list take up the lines of a page.
+pub(crate) struct Layout<'a> {
+    /// The number of entries in the list.
+    pub(crate) len: usize,
+    /// The number of lines on a page.
+    pub(crate) height: usize,
+    /// Returns the number of lines the entry at a position takes up.
+    pub(crate) entry_height: Box<dyn Fn(usize) -> usize + 'a>,
+}
+
+impl Layout<'_> {
+    /// The layout of a list whose entries all take up a single line.
+    pub(crate) fn lines(len: usize, height: usize) -> Self {
+        Self {
+            len,
+            height,
+            entry_height: Box::new(|_| 1),
+        }
+    }
+
+    /// Returns the position after the last entry that fits on a page starting at `start`. A page
+    /// always holds at least one entry, even if it does not fit.
+    fn page_end(&self, start: usize) -> usize {
+        let mut used = 0;
+        let mut end = start;
+        while end < self.len {
+            used += (self.entry_height)(end);
+            if used > self.height && end > start {
+                break;
+            }
+            end += 1;
+        }
+        end
+    }
+
+    /// Returns the position of the first entry on the page that ends just before `end`.
+    fn page_start_before(&self, end: usize) -> usize {
+        let mut used = 0;
+        let mut start = end.min(self.len);
+        while start > 0 {
+            used += (self.entry_height)(start - 1);
+            if used > self.height && start < end {
+                break;
+            }
+            start -= 1;
+        }
+        start
+    }
+}
+
+/// The selected entry of a list, and the page of the list that is shown.
+///
+/// The selection may be past the end of the list, such as when more entries are still to be
+/// loaded. Whoever owns the list keeps it within the list.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Pages {
+    /// The position of the selected entry.
+    pub(crate) pos: usize,
+    /// The position of the first entry on the page that is shown.
+    pub(crate) start: usize,
+}
+
+impl Pages {
+    /// Returns the positions of the entries on the page that is shown.
+    pub(crate) fn page(&self, layout: &Layout) -> Range<usize> {
+        let end = layout.page_end(self.start);
+        self.start.min(end)..end
+    }
+
+    /// Moves the page such that the selected entry is on it.
+    pub(crate) fn fit(&mut self, layout: &Layout) {
+        if self.pos < self.start {
+            self.start = layout.page_start_before(self.pos + 1);
+        } else if self.pos >= layout.page_end(self.start) {
+            self.start = self.pos;
+        }
+    }
+
+    /// Moves the selection, by a page for a step, and moves the page along with it.
+    pub(crate) fn go_to(&mut self, travel: Travel, layout: &Layout) {
+        match travel {
+            Travel::NextStep => {
+                self.start = layout.page_end(self.start);
+                self.pos = self.start;
+            }
+            Travel::PrevStep => {
+                self.start = layout.page_start_before(self.start);
+                self.pos = self.start;
+            }
+            Travel::NextItem => self.pos += 1,
+            Travel::PrevItem => self.pos -= self.pos.min(1),
+            Travel::Top => self.pos = 0,
+            Travel::Bottom => self.pos = layout.len.saturating_sub(1),
+        }
+        self.fit(layout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries of one, two and three lines, in turn.
+    fn uneven(len: usize, height: usize) -> Layout<'static> {
+        Layout {
+            len,
+            height,
+            entry_height: Box::new(|pos| pos % 3 + 1),
+        }
+    }
+
+    #[test]
+    fn pages_hold_what_fits() {
+        let layout = uneven(10, 6);
+        let mut pages = Pages::default();
+        assert_eq!(pages.page(&layout), 0..3);
+        pages.go_to(Travel::NextStep, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (3, 3..6));
+        pages.go_to(Travel::NextStep, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (6, 6..9));
+        pages.go_to(Travel::PrevStep, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (3, 3..6));
+        // An entry that is higher than the page is on a page of its own.
+        assert_eq!(Pages::default().page(&uneven(3, 1)), 0..1);
+    }
+
+    #[test]
+    fn page_follows_the_selection() {
+        let layout = Layout::lines(10, 4);
+        let mut pages = Pages::default();
+        for _ in 0..4 {
+            pages.go_to(Travel::NextItem, &layout);
+        }
+        assert_eq!((pages.pos, pages.page(&layout)), (4, 4..8));
+        pages.go_to(Travel::PrevItem, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (3, 0..4));
+        pages.go_to(Travel::Bottom, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (9, 9..10));
+        pages.go_to(Travel::Top, &layout);
+        assert_eq!((pages.pos, pages.page(&layout)), (0, 0..4));
+    }
+
+    #[test]
+    fn page_is_moved_back_to_a_selection_before_it() {
+        let layout = Layout::lines(10, 4);
+        let mut pages = Pages { pos: 2, start: 8 };
+        pages.fit(&layout);
+        assert_eq!(pages.page(&layout), 0..4);
+        let mut pages = Pages { pos: 6, start: 8 };
+        pages.fit(&layout);
+        // The page ends with the selection, rather than starting with it.
+        assert_eq!(pages.page(&layout), 3..7);
+    }
+}
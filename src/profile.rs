@@ -0,0 +1,209 @@
+use console::style;
+use scraper::{Html, Selector};
+
+use crate::hits::HitsView;
+use crate::http::{base_url, get_page};
+use crate::listing::Listing;
+use crate::story::prepend_string;
+use crate::view::View;
+use crate::wrap::wrap;
+
+/// The number of lines of the about text shown in the profile header.
+const ABOUT_LINES: usize = 3;
+
+/// The information on the profile page of a user.
+#[derive(Debug, Clone)]
+pub(crate) struct Profile {
+    user: String,
+    karma: Option<String>,
+    joined: Option<String>,
+    invited_by: Option<String>,
+    about: Option<String>,
+}
+
+impl Profile {
+    /// Parses the profile page of `user`.
+    ///
+    /// The page is a list of `label`s, each followed by the element holding its value.
+    pub(crate) fn from_html(html: &Html, user: &str) -> Self {
+        let mut profile = Self {
+            user: user.to_string(),
+            karma: None,
+            joined: None,
+            invited_by: None,
+            about: None,
+        };
+
+        for label in html.select(&Selector::parse("#inside label").unwrap()) {
+            let value = match label.next_siblings().find_map(scraper::ElementRef::wrap) {
+                Some(value) => value,
+                None => continue,
+            };
+            let text = normalize_space(value.text());
+            match label.text().collect::<String>().trim() {
+                "Karma:" => profile.karma = Some(text),
+                "Joined:" => {
+                    profile.invited_by = value
+                        .select(&Selector::parse("a").unwrap())
+                        .find_map(|a| a.value().attr("href")?.strip_prefix("/~"))
+                        .map(|user| user.to_string());
+                    profile.joined = Some(text);
+                }
+                "About:" => {
+                    // Every paragraph goes on a line of its own, links and all.
+                    let paragraphs = value
+                        .select(&Selector::parse("p").unwrap())
+                        .map(|p| normalize_space(p.text()))
+                        .filter(|p| !p.is_empty())
+                        .collect::<Vec<String>>();
+                    profile.about = Some(if paragraphs.is_empty() {
+                        text
+                    } else {
+                        paragraphs.join("\n")
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        profile
+    }
+
+    /// Renders the profile as a header of a few lines.
+    fn to_string(&self, width: usize) -> String {
+        let field = |name, value: &Option<String>| {
+            value.as_ref().map(|v| format!("{} {v}", style(name).dim()))
+        };
+        let mut lines = vec![style(&self.user).bold().to_string()];
+        lines.extend(field("karma", &self.karma));
+        lines.extend(field("joined", &self.joined));
+        lines.extend(field("invited by", &self.invited_by));
+        if let Some(about) = &self.about {
+            lines.extend(wrap(about, width).into_iter().take(ABOUT_LINES));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Joins the pieces of `text` with single spaces between the words.
+fn normalize_space<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Gets the profile of a user.
+fn get_profile(user: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+    let html = get_page(format!("{}/~{user}", base_url()))?;
+    Ok(Profile::from_html(&html, user))
+}
+
+/// The tabs of a profile view.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProfileTab {
+    Stories,
+    Comments,
+}
+
+/// A view of the profile of a user, with their recent stories and comments.
+pub(crate) struct ProfileView {
+    profile: Profile,
+    stories: View,
+    comments: HitsView,
+    tab: ProfileTab,
+}
+
+impl ProfileView {
+    /// Loads the profile of `user` and creates a view of it.
+    pub(crate) fn load(user: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            profile: get_profile(user)?,
+            stories: View::with_listing(Listing::UserStories(user.to_string())),
+            comments: HitsView::new(Listing::UserThreads(user.to_string())),
+            tab: ProfileTab::Stories,
+        })
+    }
+
+    pub(crate) fn header(&self, width: u16) -> String {
+        prepend_string(
+            &self.profile.to_string((width as usize).saturating_sub(4)),
+            "  ",
+        )
+    }
+
+    pub(crate) fn tab(&self) -> ProfileTab {
+        self.tab
+    }
+
+    pub(crate) fn toggle_tab(&mut self) {
+        self.tab = match self.tab {
+            ProfileTab::Stories => ProfileTab::Comments,
+            ProfileTab::Comments => ProfileTab::Stories,
+        }
+    }
+
+    pub(crate) fn stories(&self) -> &View {
+        &self.stories
+    }
+
+    pub(crate) fn comments(&self) -> &HitsView {
+        &self.comments
+    }
+
+    pub(crate) fn stories_mut(&mut self) -> &mut View {
+        &mut self.stories
+    }
+
+    pub(crate) fn comments_mut(&mut self) -> &mut HitsView {
+        &mut self.comments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fixture, stand_in};
+
+    #[test]
+    fn profile_from_a_saved_page() {
+        stand_in().serve_page("/~carol", &fixture("user_profile.html"));
+        let profile = get_profile("carol").unwrap();
+        assert_eq!(profile.user, "carol");
+        assert_eq!(
+            profile.karma.as_deref(),
+            Some("1234, averaging 5.67 per story/comment")
+        );
+        assert_eq!(
+            profile.joined.as_deref(),
+            Some("3 years ago by invitation from alice")
+        );
+        assert_eq!(profile.invited_by.as_deref(), Some("alice"));
+        assert_eq!(
+            profile.about.as_deref(),
+            Some("Writes compilers for a living.\nLikes green tea and long walks.")
+        );
+
+        let header = console::strip_ansi_codes(&profile.to_string(40)).into_owned();
+        assert_eq!(
+            header.lines().take(4).collect::<Vec<&str>>(),
+            [
+                "carol",
+                "karma 1234, averaging 5.67 per story/comment",
+                "joined 3 years ago by invitation from alice",
+                "invited by alice"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_left_out() {
+        let html = Html::parse_document("<div id=\"inside\"><h1>dave</h1></div>");
+        let profile = Profile::from_html(&html, "dave");
+        assert!(profile.karma.is_none() && profile.joined.is_none());
+        assert_eq!(
+            profile.to_string(0),
+            console::style("dave").bold().to_string()
+        );
+    }
+}
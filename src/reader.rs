@@ -0,0 +1,431 @@
+//! Extraction of the main content of an article from its page, for reading it in the terminal.
+//!
+//! The content is found the way readability tools do: paragraphs award points to the elements
+//! they are in, depending on how much text they hold, and the element with the most points is
+//! taken to be the article. Class names and ids such as `content` or `sidebar` count for or against
+//! an element.
+
+use console::style;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+use crate::wrap::{wrap, wrap_hanging};
+
+/// Elements that never hold any of the content.
+const SKIPPED: &[&str] = &[
+    "aside", "button", "footer", "form", "iframe", "nav", "noscript", "script", "select", "style",
+    "svg", "template", "textarea",
+];
+
+/// Parts of class names and ids of elements that are likely to hold the content.
+const POSITIVE: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "story", "text",
+];
+
+/// Parts of class names and ids of elements that are likely not to hold the content.
+const NEGATIVE: &[&str] = &[
+    "comment", "footer", "footnote", "masthead", "menu", "meta", "nav", "related", "share",
+    "sidebar", "social", "sponsor", "widget",
+];
+
+/// A block of text of an article.
+#[derive(Debug, Clone)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    /// An item of a list, nested `depth` lists deep, with its bullet or number.
+    Item {
+        depth: usize,
+        marker: String,
+        text: String,
+    },
+    Quote(String),
+    /// Preformatted text, such as code, of which the lines are kept as they are.
+    Code(String),
+}
+
+/// The readable content of an article.
+#[derive(Debug, Clone)]
+pub(crate) struct Article {
+    blocks: Vec<Block>,
+}
+
+impl Article {
+    /// Extracts the main content from the page of an article.
+    pub(crate) fn from_html(html: &Html) -> Self {
+        let mut renderer = Renderer::default();
+        if let Some(content) = find_content(html) {
+            renderer.walk(content);
+            renderer.flush();
+        }
+        Self {
+            blocks: renderer.blocks,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Renders the article to lines of at most `width` columns.
+    pub(crate) fn to_lines(&self, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut previous_was_item = false;
+        for block in &self.blocks {
+            let is_item = matches!(block, Block::Item { .. });
+            // Blocks are separated by an empty line, except for items of the same list.
+            let same_list = is_item && previous_was_item;
+            if !lines.is_empty() && !same_list {
+                lines.push(String::new());
+            }
+            previous_was_item = is_item;
+            match block {
+                Block::Heading(text) => lines.extend(
+                    wrap(text, width)
+                        .into_iter()
+                        .map(|line| style(line).bold().to_string()),
+                ),
+                Block::Paragraph(text) => lines.extend(wrap(text, width)),
+                Block::Item {
+                    depth,
+                    marker,
+                    text,
+                } => {
+                    let indent = " ".repeat(depth.saturating_sub(1) * 2);
+                    let prefix = format!("{marker} ");
+                    let prefix_width = console::measure_text_width(&prefix);
+                    let item_width = width.saturating_sub(indent.len());
+                    for (idx, line) in wrap_hanging(text, item_width, prefix_width)
+                        .into_iter()
+                        .enumerate()
+                    {
+                        lines.push(if idx == 0 {
+                            format!("{indent}{prefix}{line}")
+                        } else {
+                            format!("{indent}{line}")
+                        });
+                    }
+                }
+                Block::Quote(text) => lines.extend(
+                    wrap(text, width.saturating_sub(2))
+                        .into_iter()
+                        .map(|line| format!("{} {line}", style("│").dim())),
+                ),
+                Block::Code(text) => lines.extend(text.lines().map(|line| {
+                    let line = line.replace('\t', "    ");
+                    console::truncate_str(&format!("  {line}"), width, "…").into_owned()
+                })),
+            }
+        }
+        lines
+    }
+}
+
+/// Returns whether `element` looks like it holds the content (positive) or not (negative) by its
+/// class names and id, as a number of points.
+fn class_weight(element: ElementRef) -> f64 {
+    let value = element.value();
+    let names = value
+        .classes()
+        .chain(value.id())
+        .map(|name| name.to_lowercase())
+        .collect::<Vec<String>>();
+    let has = |parts: &[&str]| {
+        names
+            .iter()
+            .any(|name| parts.iter().any(|part| name.contains(part)))
+    };
+    let mut weight = 0.0;
+    if has(POSITIVE) {
+        weight += 25.0;
+    }
+    if has(NEGATIVE) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+/// Finds the element that holds the main content of a page.
+fn find_content(html: &Html) -> Option<ElementRef<'_>> {
+    let mut scores: HashMap<_, (ElementRef, f64)> = HashMap::new();
+    let paragraphs = Selector::parse("p, pre, td").unwrap();
+    for paragraph in html.select(&paragraphs) {
+        let text = paragraph.text().collect::<String>();
+        let len = text.trim().chars().count();
+        if len < 25 {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+        // The parent gets the full score, and the grandparent half of it.
+        let ancestors = paragraph.ancestors().filter_map(ElementRef::wrap).take(2);
+        for (generation, ancestor) in ancestors.enumerate() {
+            let entry = scores
+                .entry(ancestor.id())
+                .or_insert_with(|| (ancestor, initial_score(ancestor)));
+            entry.1 += score / (generation + 1) as f64;
+        }
+    }
+
+    let best = scores
+        .into_values()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(element, _)| element);
+    // Pages without paragraphs may still mark their content.
+    best.or_else(|| {
+        let marked = Selector::parse("article, main, [role=main], body").unwrap();
+        html.select(&marked).next()
+    })
+}
+
+/// The score an element starts out with, before the paragraphs in it are counted.
+fn initial_score(element: ElementRef) -> f64 {
+    let score = match element.value().name() {
+        "article" | "main" => 10.0,
+        "div" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    score + class_weight(element)
+}
+
+/// Collapses the whitespace in the text of `element`, as a browser would show it.
+fn collapsed_text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Turns the elements of an article into blocks of text.
+#[derive(Default)]
+struct Renderer {
+    blocks: Vec<Block>,
+    /// The text of the block that is being collected.
+    inline: String,
+    /// The lists the renderer is in, with the number of the next item for ordered lists.
+    lists: Vec<Option<usize>>,
+    /// The marker for the list item that is being collected.
+    marker: Option<String>,
+    /// The number of quotes the renderer is in.
+    quotes: usize,
+}
+
+impl Renderer {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child)
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if SKIPPED.contains(&name) || element.value().attr("hidden").is_some() {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let text = collapsed_text(element);
+                if !text.is_empty() {
+                    self.blocks.push(Block::Heading(text));
+                }
+            }
+            "pre" => {
+                self.flush();
+                let text = element.text().collect::<String>();
+                let text = text.trim_matches('\n');
+                if !text.trim().is_empty() {
+                    self.blocks.push(Block::Code(text.to_string()));
+                }
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.lists.push((name == "ol").then_some(1));
+                self.walk(element);
+                self.flush();
+                self.lists.pop();
+            }
+            "li" => {
+                self.flush();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                });
+                self.walk(element);
+                self.flush();
+            }
+            "blockquote" => {
+                self.flush();
+                self.quotes += 1;
+                self.walk(element);
+                self.flush();
+                self.quotes -= 1;
+            }
+            "br" => self.inline.push('\n'),
+            "hr" | "img" | "picture" | "video" | "audio" => self.flush(),
+            "strong" | "b" => self.push_styled(element, |text| style(text).bold().to_string()),
+            "em" | "i" => self.push_styled(element, |text| style(text).italic().to_string()),
+            "code" | "kbd" => self.push_styled(element, |text| style(text).cyan().to_string()),
+            "td" | "th" => {
+                self.walk(element);
+                self.push_text(" ");
+            }
+            "p" | "div" | "section" | "article" | "main" | "header" | "table" | "tr" | "dl"
+            | "dt" | "dd" | "figure" | "figcaption" | "details" | "summary" => {
+                self.flush();
+                self.walk(element);
+                self.flush();
+            }
+            _ => self.walk(element),
+        }
+    }
+
+    /// Adds text to the block that is being collected, with its whitespace collapsed.
+    fn push_text(&mut self, text: &str) {
+        let starts_with_space = text.starts_with(char::is_whitespace);
+        let ends_with_space = text.ends_with(char::is_whitespace);
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        if words.is_empty() {
+            if starts_with_space {
+                self.push_space();
+            }
+            return;
+        }
+        if starts_with_space {
+            self.push_space();
+        }
+        self.inline.push_str(&words.join(" "));
+        if ends_with_space {
+            self.push_space();
+        }
+    }
+
+    /// Adds the text of `element` in the style set by `styled`.
+    fn push_styled(&mut self, element: ElementRef, styled: impl Fn(String) -> String) {
+        let text = collapsed_text(element);
+        if !text.is_empty() {
+            self.inline.push_str(&styled(text));
+        }
+    }
+
+    fn push_space(&mut self) {
+        if !self.inline.is_empty() && !self.inline.ends_with([' ', '\n']) {
+            self.inline.push(' ');
+        }
+    }
+
+    /// Ends the block that is being collected.
+    fn flush(&mut self) {
+        let text = self.inline.trim().to_string();
+        self.inline.clear();
+        if text.is_empty() {
+            return;
+        }
+        let block = if let Some(marker) = self.marker.take() {
+            Block::Item {
+                depth: self.lists.len(),
+                marker,
+                text,
+            }
+        } else if !self.lists.is_empty() {
+            // Text that continues an item after a nested block.
+            Block::Item {
+                depth: self.lists.len(),
+                marker: " ".to_string(),
+                text,
+            }
+        } else if self.quotes > 0 {
+            Block::Quote(text)
+        } else {
+            Block::Paragraph(text)
+        };
+        self.blocks.push(block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::document;
+
+    fn plain_lines(article: &Article, width: usize) -> Vec<String> {
+        article
+            .to_lines(width)
+            .iter()
+            .map(|line| console::strip_ansi_codes(line).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn article_from_a_saved_page() {
+        // The masthead, menu, sidebar and footer are left out, and so are the hidden paragraph,
+        // the scripts and the share button in the article.
+        let article = Article::from_html(&document("article.html"));
+        assert_eq!(
+            plain_lines(&article, 60),
+            [
+                "Borrowing without tears",
+                "",
+                "The borrow checker is the part of the compiler that people",
+                "new to Rust run into first, and often the part they struggle",
+                "with the longest.",
+                "",
+                "There are only a few rules, and once they click, most errors",
+                "read like the book explained them all along.",
+                "",
+                "• One mutable borrow",
+                "• Any number of shared borrows",
+                "  1. Not at the same time",
+                "  2. Not longer than the owner",
+                "",
+                "│ Fighting the borrow checker, and then winning.",
+                "",
+                "  fn main() {",
+                "      let s = String::new();",
+                "      let t = &s;",
+                "  }",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_items_and_code_fit_the_width() {
+        let html = Html::parse_document(
+            "<main><ol><li>An item that does not fit on one line</li></ol>\
+             <pre>let answer = 42;</pre></main>",
+        );
+        let article = Article::from_html(&html);
+        assert_eq!(
+            plain_lines(&article, 20),
+            [
+                "1. An item that does",
+                "   not fit on one",
+                "   line",
+                "",
+                "  let answer = 42;",
+            ]
+        );
+        assert_eq!(plain_lines(&article, 10).last().unwrap(), "  let ans…");
+    }
+
+    #[test]
+    fn pages_without_content_are_empty() {
+        let html = Html::parse_document("<body><nav><p>Home</p></nav><script>x()</script></body>");
+        assert!(Article::from_html(&html).is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+/// A search query entered through the `/` prompt.
+///
+/// Matching is case-insensitive and happens on the visible text, so ANSI escape sequences in
+/// rendered output never produce or break up a match.
+#[derive(Debug, Clone)]
+pub(crate) struct Search {
+    query: String,
+}
+
+impl Search {
+    /// Creates a new search. Returns `None` if the query is empty.
+    pub(crate) fn new(query: &str) -> Option<Self> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        Some(Self {
+            query: lowercase(query),
+        })
+    }
+
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns whether `haystack` contains the query, ignoring case.
+    pub(crate) fn matches(&self, haystack: &str) -> bool {
+        lowercase(haystack).contains(&self.query)
+    }
+
+    /// Returns whether the visible text of an escaped line contains the query.
+    pub(crate) fn matches_escaped(&self, line: &str) -> bool {
+        self.matches(&console::strip_ansi_codes(line))
+    }
+
+    /// Wraps every match of the query in the visible text of `line` in reverse video.
+    ///
+    /// Escape sequences that already exist in the line are left in place.
+    pub(crate) fn highlight(&self, line: &str) -> String {
+        let visible = visible_chars(line);
+
+        let query: Vec<char> = self.query.chars().collect();
+        let lower: Vec<char> = visible.iter().map(|&(_, c)| lowercase_char(c)).collect();
+
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i + query.len() <= lower.len() {
+            if lower[i..i + query.len()] == query[..] {
+                let start = visible[i].0;
+                let (last_idx, last_char) = visible[i + query.len() - 1];
+                ranges.push((start, last_idx + last_char.len_utf8()));
+                i += query.len();
+            } else {
+                i += 1;
+            }
+        }
+
+        mark_ranges(line, &ranges, "\u{1b}[7m", "\u{1b}[27m")
+    }
+}
+
+/// Lowercases `text` one character at a time, such that every character stays one character and
+/// the characters of a match can be found back in the text. `String::to_lowercase` turns some
+/// characters into several, such as `İ` into `i̇`.
+fn lowercase(text: &str) -> String {
+    text.chars().map(lowercase_char).collect()
+}
+
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Returns the visible characters of `line`, which may contain ANSI escape sequences, together
+/// with their byte offsets in the line.
+pub(crate) fn visible_chars(line: &str) -> Vec<(usize, char)> {
+    let mut visible: Vec<(usize, char)> = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip over a CSI sequence, which ends at the first byte in the range '@'..='~'.
+            if let Some((_, '[')) = chars.peek() {
+                chars.next();
+                for (_, c) in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        visible.push((idx, c));
+    }
+    visible
+}
+
+/// Wraps the byte ranges `ranges` of `line` in the escape sequences `start` and `end`. The ranges
+/// are in order and do not overlap.
+pub(crate) fn mark_ranges(line: &str, ranges: &[(usize, usize)], start: &str, end: &str) -> String {
+    let mut marked = String::with_capacity(line.len());
+    let mut prev = 0;
+    for &(range_start, range_end) in ranges {
+        marked.push_str(&line[prev..range_start]);
+        marked.push_str(start);
+        marked.push_str(&line[range_start..range_end]);
+        marked.push_str(end);
+        prev = range_end;
+    }
+    marked.push_str(&line[prev..]);
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_ignoring_case() {
+        let search = Search::new("  Rust ").unwrap();
+        assert_eq!(search.query(), "rust");
+        assert!(search.matches("Learning RUST"));
+        assert!(!search.matches("Learning Go"));
+        assert!(Search::new("   ").is_none());
+    }
+
+    #[test]
+    fn matches_the_visible_text_of_a_line() {
+        let search = Search::new("bold text").unwrap();
+        assert!(search.matches_escaped("\u{1b}[1mbold\u{1b}[0m text"));
+        // The escape sequences themselves are not searched.
+        assert!(!Search::new("1m").unwrap().matches_escaped("\u{1b}[1mbold"));
+    }
+
+    #[test]
+    fn highlights_matches_around_escape_sequences() {
+        let search = Search::new("ab").unwrap();
+        assert_eq!(
+            search.highlight("xAB ab"),
+            "x\u{1b}[7mAB\u{1b}[27m \u{1b}[7mab\u{1b}[27m"
+        );
+        assert_eq!(
+            search.highlight("\u{1b}[1ma\u{1b}[0mb"),
+            "\u{1b}[1m\u{1b}[7ma\u{1b}[0mb\u{1b}[27m"
+        );
+        assert_eq!(search.highlight("none here"), "none here");
+    }
+
+    #[test]
+    fn characters_with_a_longer_lowercase_line_up() {
+        // `İ` lowercases to two characters with `String::to_lowercase`.
+        let search = Search::new("İst").unwrap();
+        assert!(search.matches("İSTANBUL"));
+        assert_eq!(
+            search.highlight("İİstanbul"),
+            "İ\u{1b}[7mİst\u{1b}[27manbul"
+        );
+        let search = Search::new("bul").unwrap();
+        assert!(search.matches("İstanbul"));
+        assert_eq!(search.highlight("İstanbul"), "İstan\u{1b}[7mbul\u{1b}[27m");
+    }
+}
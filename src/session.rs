@@ -0,0 +1,96 @@
+use scraper::{Html, Selector};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::hits::HITS_SELECTOR;
+use crate::http::{base_url, get_page, get_page_quietly, has_session};
+
+/// The number of replies to the logged in user they have not read yet, as last checked.
+static UNREAD_REPLIES: AtomicUsize = AtomicUsize::new(0);
+
+/// The error for doing something that requires logging in without being logged in.
+pub(crate) const NOT_LOGGED_IN: &str = "not logged in, see the session settings";
+
+/// The personal state of the user that is logged in with the session cookie.
+#[derive(Debug)]
+pub(crate) struct Session {
+    user: String,
+    /// The tags the user has filtered out of the front page and the newest stories.
+    filtered_tags: Vec<String>,
+}
+
+impl Session {
+    /// Logs in with the configured session cookie, and loads the settings of the user that change
+    /// what the site shows. Returns `None` if no session cookie is configured.
+    pub(crate) fn load() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if !has_session() {
+            return Ok(None);
+        }
+        // The settings page is only shown to users that are logged in; everyone else is sent to
+        // the login page.
+        let settings = get_page(format!("{}/settings", base_url()))?;
+        let user = match user_from_html(&settings) {
+            Some(user) => user,
+            None => return Err("not logged in, the session cookie may have expired".into()),
+        };
+        let filters = get_page(format!("{}/filters", base_url()))?;
+        let unread = get_page(format!("{}/replies/unread", base_url()))?;
+        UNREAD_REPLIES.store(unread_replies_from_html(&unread), Ordering::SeqCst);
+        Ok(Some(Self {
+            user,
+            filtered_tags: filtered_tags_from_html(&filters),
+        }))
+    }
+
+    pub(crate) fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub(crate) fn filtered_tags(&self) -> &[String] {
+        &self.filtered_tags
+    }
+}
+
+pub(crate) fn unread_replies() -> usize {
+    UNREAD_REPLIES.load(Ordering::SeqCst)
+}
+
+/// Checks for unread replies every `interval` in the background, for as long as the program runs.
+///
+/// Only the number is kept here, the main loop draws it in the status bar. Failed checks are
+/// ignored, the next one may succeed.
+pub(crate) fn poll_replies(interval: Duration) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        let html = match get_page_quietly(format!("{}/replies/unread", base_url())) {
+            Ok(html) => html,
+            Err(_) => continue,
+        };
+        UNREAD_REPLIES.store(unread_replies_from_html(&html), Ordering::SeqCst);
+    });
+}
+
+/// Counts the replies on the page of unread replies.
+fn unread_replies_from_html(html: &Html) -> usize {
+    let selector = Selector::parse(HITS_SELECTOR).unwrap();
+    html.select(&selector).count()
+}
+
+/// Reads the name of the user from their settings page.
+fn user_from_html(html: &Html) -> Option<String> {
+    let selector = Selector::parse("input#user_username").unwrap();
+    let input = html.select(&selector).next()?;
+    Some(input.value().attr("value")?.to_string())
+}
+
+/// Reads the tags that are checked on the filters page, which look like
+/// `<input type="checkbox" name="tags[job]" checked>`.
+fn filtered_tags_from_html(html: &Html) -> Vec<String> {
+    let selector = Selector::parse(r#"input[type="checkbox"][name^="tags["][checked]"#).unwrap();
+    html.select(&selector)
+        .filter_map(|input| {
+            let name = input.value().attr("name")?;
+            Some(name.strip_prefix("tags[")?.strip_suffix(']')?.to_string())
+        })
+        .collect()
+}
@@ -0,0 +1,69 @@
+//! The settings of the program, which are made from the configuration once at startup.
+//!
+//! Everything the program reads from the configuration while it runs is kept here, such as the
+//! client for requests and the saved filters. Reading the settings before [`init`] is a bug, which
+//! panics rather than going on with the defaults.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::config::{ArchiveConfig, Config, DiscussionsConfig};
+use crate::filter::{self, Filter};
+use crate::http::Client;
+use crate::mutes::{self, Mutes};
+use crate::source::SourceSettings;
+use crate::words::Words;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+pub(crate) struct Settings {
+    /// The client all requests are sent with.
+    pub(crate) client: Client,
+    pub(crate) source: SourceSettings,
+    pub(crate) archive: ArchiveConfig,
+    pub(crate) discussions: DiscussionsConfig,
+    /// The saved filters, by name.
+    pub(crate) filters: HashMap<String, Filter>,
+    pub(crate) words: Words,
+    pub(crate) mutes: Mutes,
+}
+
+impl Settings {
+    /// Makes the settings from `config`, with the mute lists `mutes`.
+    pub(crate) fn new(config: &Config, mutes: Mutes) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            client: Client::new(config)?,
+            source: SourceSettings::new(config)?,
+            archive: config.archive.clone(),
+            discussions: config.discussions.clone(),
+            filters: filter::parse_saved(&config.filters)?,
+            words: Words::new(&config.words)?,
+            mutes,
+        })
+    }
+}
+
+/// Makes the settings from `config`, with the mute lists from their file, and keeps them for the
+/// rest of the run.
+pub(crate) fn init(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    set(Settings::new(config, Mutes::load(mutes::path())?)?)
+}
+
+/// Keeps `settings` for the rest of the run.
+pub(crate) fn set(settings: Settings) -> Result<(), Box<dyn std::error::Error>> {
+    if SETTINGS.set(settings).is_err() {
+        return Err("the settings are already set up".into());
+    }
+    Ok(())
+}
+
+/// Returns the settings.
+///
+/// # Panics
+///
+/// If the settings are not set up yet.
+pub(crate) fn settings() -> &'static Settings {
+    SETTINGS
+        .get()
+        .expect("the settings are read before they are set up")
+}
@@ -0,0 +1,298 @@
+//! The sites stories are read from.
+
+use scraper::Selector;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::config::{Config, MergedConfig};
+use crate::feed;
+use crate::http::{base_url, get_page};
+use crate::listing::Listing;
+use crate::merged;
+use crate::settings::settings;
+use crate::story::Story;
+use crate::STORIES_PER_SITE_PAGE;
+
+/// The number of stories Hacker News shows on one page.
+const HACKER_NEWS_STORIES_PER_PAGE: usize = 30;
+
+/// The settings of the sources.
+pub(crate) struct SourceSettings {
+    /// The source of the front page.
+    selected: Source,
+    /// The base URL of Hacker News, without a trailing slash.
+    hacker_news_url: String,
+    /// The sources of the merged front page, and how its stories are ranked.
+    merged: MergedConfig,
+}
+
+impl SourceSettings {
+    /// Reads the source of the front page and the base URLs of the sources from the
+    /// configuration.
+    pub(crate) fn new(config: &Config) -> Result<Self, String> {
+        if config.merged.sources.contains(&Source::Merged) {
+            return Err("the merged sources cannot include \"merged\" itself".to_string());
+        }
+        Ok(Self {
+            selected: config.site.source.clone(),
+            hacker_news_url: config.hacker_news.url.trim_end_matches('/').to_string(),
+            merged: config.merged.clone(),
+        })
+    }
+}
+
+/// Returns the source of the front page, as set in the configuration or on the command line.
+pub(crate) fn selected() -> Source {
+    settings().source.selected.clone()
+}
+
+/// Returns the base URL of Hacker News, without a trailing slash.
+pub(crate) fn hacker_news_url() -> &'static str {
+    &settings().source.hacker_news_url
+}
+
+/// Returns the settings of the merged front page.
+pub(crate) fn merged_config() -> &'static MergedConfig {
+    &settings().source.merged
+}
+
+/// A site that stories and their comments are read from.
+///
+/// Everything the logged in user can do, and all listings other than the front page, are only
+/// available on lobste.rs.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) enum Source {
+    #[default]
+    Lobsters,
+    HackerNews,
+    /// Another site that runs the same software as lobste.rs, by its base url.
+    Instance(String),
+    /// An RSS or Atom feed, by its url.
+    Feed(String),
+    /// The front pages of the sources in the `[merged]` configuration, in one list.
+    Merged,
+}
+
+impl Source {
+    /// Returns the source with the comment thread at `url`, if its comments can be read. Other
+    /// instances are only known when they are the selected source or one of the merged sources.
+    pub(crate) fn of_thread(url: &str) -> Option<Source> {
+        if url.starts_with(base_url()) {
+            Some(Source::Lobsters)
+        } else if url.starts_with(hacker_news_url()) {
+            Some(Source::HackerNews)
+        } else {
+            std::iter::once(&settings().source.selected)
+                .chain(&merged_config().sources)
+                .find(|source| matches!(source, Source::Instance(site) if url.starts_with(site.as_str())))
+                .cloned()
+        }
+    }
+
+    /// Returns the base URL of the site of this source, if it is a site rather than a feed.
+    pub(crate) fn site_url(&self) -> Option<&str> {
+        match self {
+            Source::Lobsters => Some(base_url()),
+            Source::HackerNews => Some(hacker_news_url()),
+            Source::Instance(url) => Some(url),
+            Source::Feed(_) | Source::Merged => None,
+        }
+    }
+
+    /// Returns the short name the source is marked with in the merged front page.
+    pub(crate) fn badge(&self) -> String {
+        match self {
+            Source::Lobsters => "lobste.rs".to_string(),
+            Source::HackerNews => "HN".to_string(),
+            Source::Instance(url) | Source::Feed(url) => {
+                let host = reqwest::Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string));
+                host.unwrap_or_else(|| feed::name(url).to_string())
+            }
+            Source::Merged => "merged".to_string(),
+        }
+    }
+
+    /// Returns the url of a page of `listing` on this source, or `None` if the source has no such
+    /// page.
+    ///
+    /// ## Note
+    ///
+    /// The page numbers are 1-indexed.
+    fn listing_url(&self, listing: &Listing, page: usize) -> Option<String> {
+        match self {
+            Source::Lobsters => listing.url(page),
+            Source::HackerNews => match listing {
+                Listing::Front => Some(format!("{}/news?p={page}", hacker_news_url())),
+                Listing::Thread(url) if page == 1 => Some(url.clone()),
+                _ => None,
+            },
+            Source::Instance(site) => match listing {
+                Listing::Front => Some(format!("{site}/page/{page}")),
+                Listing::Thread(url) if page == 1 => Some(url.clone()),
+                _ => None,
+            },
+            // A feed has a single page, which is its front page.
+            Source::Feed(url) => match listing {
+                Listing::Front if page == 1 => Some(url.clone()),
+                _ => None,
+            },
+            // The merged front page is read from the front pages of its sources.
+            Source::Merged => None,
+        }
+    }
+
+    /// The number of stories the source shows on one page of `listing`.
+    pub(crate) fn per_page(&self, listing: &Listing) -> usize {
+        match self {
+            Source::Lobsters | Source::Instance(_) => listing.per_page(),
+            Source::HackerNews => match listing {
+                Listing::Front => HACKER_NEWS_STORIES_PER_PAGE,
+                _ => STORIES_PER_SITE_PAGE,
+            },
+            // All items of a feed are on its one page.
+            Source::Feed(_) => usize::MAX,
+            Source::Merged => merged_config()
+                .sources
+                .iter()
+                .map(|source| source.per_page(listing))
+                .fold(0, usize::saturating_add),
+        }
+    }
+
+    /// Gets a page of the stories of `listing` from this source.
+    pub(crate) fn get_stories(
+        &self,
+        listing: &Listing,
+        page: usize,
+    ) -> Result<Vec<Story>, Box<dyn std::error::Error>> {
+        if let (Source::Merged, Listing::Front) = (self, listing) {
+            return merged::get_stories(merged_config(), page);
+        }
+        let url = match self.listing_url(listing, page) {
+            Some(url) => url,
+            None => return Ok(Vec::new()),
+        };
+        if let Source::Feed(_) = self {
+            return feed::get_stories(self, url);
+        }
+        let html = get_page(url)?;
+        Ok(match self {
+            Source::Lobsters | Source::Instance(_) => {
+                let stories_selector =
+                    Selector::parse("ol.stories > .story > .story_liner").unwrap();
+                html.select(&stories_selector)
+                    .filter_map(|story| Story::from_site_html(story, self.clone()))
+                    .collect()
+            }
+            Source::HackerNews => {
+                let rows_selector = Selector::parse("tr.athing").unwrap();
+                html.select(&rows_selector)
+                    .filter_map(Story::from_hacker_news_html)
+                    .collect()
+            }
+            Source::Feed(_) | Source::Merged => unreachable!("these are read above"),
+        })
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lobsters" => Ok(Source::Lobsters),
+            "hackernews" | "hn" => Ok(Source::HackerNews),
+            "merged" => Ok(Source::Merged),
+            // Other instances are written as `lobsters:<url>`, such as `lobsters:https://tilde.news`.
+            s if s.starts_with("lobsters:") && s.contains("://") => Ok(Source::Instance(
+                s["lobsters:".len()..].trim_end_matches('/').to_string(),
+            )),
+            url if url.contains("://") => Ok(Source::Feed(url.to_string())),
+            _ => Err(format!(
+                "unknown source {s:?}, expected \"lobsters\", \"hackernews\", \"merged\", \"lobsters:<url>\" or the url of a feed"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Source {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Source::from_str(&s)
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Lobsters => write!(f, "lobste.rs"),
+            Source::HackerNews => write!(f, "Hacker News"),
+            Source::Instance(url) | Source::Feed(url) => write!(f, "{}", feed::name(url)),
+            Source::Merged => {
+                let names = merged_config()
+                    .sources
+                    .iter()
+                    .map(Source::to_string)
+                    .collect::<Vec<String>>();
+                write!(f, "{}", names.join(" + "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fixture, stand_in, COOKIE};
+
+    /// Returns the session cookies sent with the requests for `path` to `host`.
+    fn cookies_sent(path: &str, host: &str) -> Vec<Option<String>> {
+        stand_in()
+            .requests_to(path)
+            .into_iter()
+            .filter(|request| request.headers.get("host").map(String::as_str) == Some(host))
+            .map(|request| request.headers.get("cookie").cloned())
+            .collect()
+    }
+
+    #[test]
+    fn session_cookie_is_only_sent_to_the_site() {
+        // The paths are only used by this test, as the stand-in is shared by all tests.
+        let site = stand_in().url("");
+        let other = stand_in().other_url("");
+        let host = |url: &str| url.split_once("://").unwrap().1.to_string();
+        let stories = fixture("search_stories.html");
+        stand_in().serve_page("/~cookie_monster/stories", &stories);
+        stand_in().serve_page("/cookie_instance/page/1", &stories);
+        stand_in().serve(
+            "/cookie_feed.xml",
+            200,
+            "application/rss+xml",
+            "<rss version=\"2.0\"><channel><title>Feed</title><item><title>An item</title>\
+             <link>https://example.com/item</link></item></channel></rss>",
+        );
+
+        let listing = Listing::UserStories("cookie_monster".to_string());
+        assert_eq!(Source::Lobsters.get_stories(&listing, 1).unwrap().len(), 2);
+        let instance = Source::Instance(format!("{other}/cookie_instance"));
+        assert_eq!(instance.get_stories(&Listing::Front, 1).unwrap().len(), 2);
+        let feed = Source::Feed(format!("{other}/cookie_feed.xml"));
+        assert_eq!(feed.get_stories(&Listing::Front, 1).unwrap().len(), 1);
+
+        let cookie = Some(format!("lobster_trap={COOKIE}"));
+        assert_eq!(
+            cookies_sent("/~cookie_monster/stories", &host(&site)),
+            [cookie]
+        );
+        assert_eq!(
+            cookies_sent("/cookie_instance/page/1", &host(&other)),
+            [None]
+        );
+        assert_eq!(cookies_sent("/cookie_feed.xml", &host(&other)), [None]);
+    }
+}
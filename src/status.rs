@@ -0,0 +1,120 @@
+use console::{style, Term};
+use std::time::Instant;
+
+use crate::frame;
+
+fn format_unread_replies(unread: usize) -> String {
+    match unread {
+        1 => "1 unread reply".to_string(),
+        n => format!("{n} unread replies"),
+    }
+}
+
+/// Marks the start of a request to `url`.
+///
+/// Requests are sent while the screen waits for them, so a loading indicator is drawn over the
+/// status bar, which is drawn again on the next frame. When the interface is not shown, such as
+/// for commands on the command line, nothing is drawn.
+pub(crate) fn fetch_started(url: &str) {
+    if !frame::alternate_screen_open() {
+        return;
+    }
+    #[cfg(debug_assertions)]
+    let indicator = format!(" loading ({url}) … ");
+    #[cfg(not(debug_assertions))]
+    let indicator = {
+        let _ = url;
+        " loading … ".to_string()
+    };
+    // Save the cursor, draw the indicator at the top left and restore the cursor.
+    let _ = Term::stdout().write_str(&format!(
+        "\x1b7\x1b[1;1H{}\x1b8",
+        style(indicator).reverse()
+    ));
+}
+
+/// The contents of the status bar at the top of the screen.
+#[derive(Debug, Default)]
+pub(crate) struct Status {
+    /// The name of the listing that is shown.
+    pub(crate) listing: String,
+    /// The position of the selection and the number of loaded entries.
+    pub(crate) position: Option<(usize, usize)>,
+    /// The site page the selection is on.
+    pub(crate) page: Option<usize>,
+    /// When the entries were first loaded.
+    pub(crate) loaded_at: Option<Instant>,
+    /// A description of the active search or filter.
+    pub(crate) filter: Option<String>,
+    /// The number of listed stories with a watch word.
+    pub(crate) watched: usize,
+    /// The tags the site filters out of the listing for the logged in user.
+    pub(crate) filtered_tags: Vec<String>,
+    /// The name of the logged in user.
+    pub(crate) user: Option<String>,
+    /// The number of replies to the logged in user they have not read yet.
+    pub(crate) unread_replies: usize,
+    /// The last error that occurred.
+    pub(crate) error: Option<String>,
+}
+
+impl Status {
+    pub(crate) fn new(listing: String) -> Self {
+        Self {
+            listing,
+            ..Default::default()
+        }
+    }
+
+    /// Renders the status bar as a single line of exactly `width` columns.
+    pub(crate) fn to_string(&self, width: usize) -> String {
+        let mut parts = vec![self.listing.clone()];
+        if let Some((pos, loaded)) = self.position {
+            parts.push(format!("{}/{loaded}", (pos + 1).min(loaded)));
+        }
+        if let Some(page) = self.page {
+            parts.push(format!("page {page}"));
+        }
+        if let Some(loaded_at) = self.loaded_at {
+            parts.push(format!("loaded {}", format_age(loaded_at)));
+        }
+        if let Some(filter) = &self.filter {
+            parts.push(filter.clone());
+        }
+        if self.watched > 0 {
+            parts.push(format!("{} watched", self.watched));
+        }
+        if !self.filtered_tags.is_empty() {
+            parts.push(format!("filtering {}", self.filtered_tags.join(" ")));
+        }
+        if let Some(user) = &self.user {
+            parts.push(format!("~{user}"));
+        }
+        if self.unread_replies > 0 {
+            parts.push(format_unread_replies(self.unread_replies));
+        }
+        parts.push("? help".to_string());
+
+        let mut line = format!(" {}", parts.join(" · "));
+        if let Some(error) = &self.error {
+            line = format!("{line} · error: {error}");
+        }
+        let line = console::pad_str(&line, width, console::Alignment::Left, Some("…"));
+        let line = style(line).reverse();
+        match self.error {
+            Some(_) => line.red().to_string(),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// Formats the time since `instant` in a short, human readable way.
+fn format_age(instant: Instant) -> String {
+    let secs = instant.elapsed().as_secs();
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
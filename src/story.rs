@@ -1,6 +1,15 @@
-use crate::{get_page, Tag, URL};
+use crate::discussions::{self, HackerNewsHit};
+use crate::feed::{time_ago, FeedItem};
+use crate::http::{base_url, get_page, post_form};
+use crate::merged::normalize_url;
+use crate::search::Search;
+use crate::source::{hacker_news_url, Source};
+use crate::words;
+use crate::wrap::{wrap, wrap_hanging};
+use crate::Tag;
 use console::style;
-use scraper::{ElementRef, Selector};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
 use std::fmt::Display;
 use std::str::FromStr;
 
@@ -26,17 +35,25 @@ impl Display for Byline {
 }
 
 impl Byline {
+    /// Returns the name of the user in the byline, if it is known.
+    pub(crate) fn user(&self) -> Option<&str> {
+        match self {
+            Byline::AuthoredBy(user) | Byline::Via(user) => Some(user),
+            Byline::Unknown => None,
+        }
+    }
+
     fn from_html(html: ElementRef) -> Self {
         // Take the third element, which contains 'via' or 'authored by'.
-        let t = html.text().nth(2).unwrap();
+        let t = html.text().nth(2).unwrap_or_default();
 
-        let user = html
+        let Some(user) = html
             .select(&Selector::parse(".u-author").unwrap())
             .next()
-            .unwrap()
-            .text()
-            .next()
-            .unwrap();
+            .and_then(|author| author.text().next())
+        else {
+            return Self::Unknown;
+        };
 
         match t.trim() {
             "via" => Self::Via(user.to_string()),
@@ -51,7 +68,12 @@ impl Byline {
 
 #[derive(Debug, Clone)]
 pub(crate) struct Comment {
-    votes: usize,
+    /// The id of the comment on the site.
+    short_id: String,
+    /// The score of the comment, if the site shows it.
+    votes: Option<usize>,
+    /// Whether the logged in user has upvoted the comment.
+    upvoted: bool,
     author: String,
     time: String,
     content: String,
@@ -68,16 +90,17 @@ impl Comment {
                 .next()
         };
 
-        let id = html
+        let comment = html
             .select(&Selector::parse(".comment").unwrap())
             .next()
             .unwrap()
-            .value()
-            .id()
-            .unwrap();
+            .value();
+        let id = comment.id().unwrap();
 
         Self {
-            votes: usize::from_str(s(".comment .voters .score").unwrap()).unwrap_or(0),
+            short_id: id.strip_prefix("c_").unwrap_or(id).to_string(),
+            votes: Some(usize::from_str(s(".comment .voters .score").unwrap()).unwrap_or(0)),
+            upvoted: comment.classes().any(|class| class == "upvoted"),
             author: html
                 .select(&Selector::parse(".comment .details .byline a").unwrap())
                 .nth(2)
@@ -114,46 +137,160 @@ impl Comment {
         }
     }
 
+    /// Parses a comment from its row on a Hacker News item page, together with how deep it is
+    /// nested. The comments are listed one after the other there, and only their indentation
+    /// tells which comment one replies to.
+    fn from_hacker_news_html(row: ElementRef) -> Option<(usize, Self)> {
+        let first_text = |selector| {
+            row.select(&Selector::parse(selector).unwrap())
+                .next()
+                .map(|element| element.text().collect::<String>().trim().to_string())
+        };
+        let depth = row
+            .select(&Selector::parse("td.ind").unwrap())
+            .next()
+            .and_then(|ind| ind.value().attr("indent"))
+            .and_then(|indent| usize::from_str(indent).ok())
+            .unwrap_or(0);
+        // Paragraphs after the first are in `p` elements of their own.
+        let content = row
+            .select(&Selector::parse(".commtext").unwrap())
+            .next()
+            .map(|text| {
+                let mut content = String::new();
+                for child in text.children() {
+                    match ElementRef::wrap(child) {
+                        Some(element) if element.value().name() == "p" => {
+                            content.push_str("\n\n");
+                            content.extend(element.text());
+                        }
+                        Some(element) => content.extend(element.text()),
+                        None => content.push_str(child.value().as_text().map_or("", |t| t)),
+                    }
+                }
+                content
+            });
+
+        Some((
+            depth,
+            Self {
+                short_id: row.value().id()?.to_string(),
+                votes: None,
+                upvoted: false,
+                author: first_text(".hnuser").unwrap_or_else(|| "[deleted]".to_string()),
+                time: first_text(".age").unwrap_or_default(),
+                content: content.unwrap_or_else(|| "[deleted]".to_string()),
+                children: Vec::new(),
+            },
+        ))
+    }
+
     pub fn to_string(&self, width: usize) -> String {
-        let indent = "│   ";
-        let byline = style(format!("({}) {} {}", self.votes, self.author, self.time)).dim();
-        let content = wrap(self.content.trim().to_string(), width);
-        let children = &self
-            .children
-            .iter()
-            .map(|comment| {
-                prepend_string(
-                    &comment.to_string(width - indent.len() + 2),
-                    &style(indent).dim().to_string(),
-                )
-            })
+        self.to_lines(width, &|_| false)
+            .into_iter()
+            .map(|(line, _)| line)
             .collect::<Vec<String>>()
-            .join("\n");
-        format!("{byline}\n{content}\n{children}")
+            .join("\n")
+    }
+
+    /// Renders the comment and its children to lines, each together with the comment the line
+    /// belongs to. The comments for which `collapsed` holds are rendered as a single line, without
+    /// their children.
+    pub(crate) fn to_lines(
+        &self,
+        width: usize,
+        collapsed: &dyn Fn(&Comment) -> bool,
+    ) -> Vec<(String, &Comment)> {
+        let indent = "│   ";
+        if collapsed(self) {
+            let replies = self.count_replies();
+            let line = format!(
+                "muted comment by {}, {replies} repl{} (e to show)",
+                self.author,
+                if replies == 1 { "y" } else { "ies" }
+            );
+            return vec![(style(line).dim().italic().to_string(), self)];
+        }
+        let votes = match self.votes {
+            Some(votes) if self.upvoted => format!("({}) ", style(format!("▲{votes}")).red()),
+            Some(votes) => format!("({votes}) "),
+            None => String::new(),
+        };
+        let byline = style(format!("{votes}{} {}", self.author, self.time)).dim();
+        // Watch words are highlighted before wrapping, such that a phrase that ends up on two
+        // lines is still found.
+        let content = wrap(&words::highlight(self.content.trim()), width);
+        let mut lines = vec![(byline.to_string(), self)];
+        lines.extend(content.into_iter().map(|line| (line, self)));
+        for child in &self.children {
+            lines.extend(
+                child
+                    .to_lines(
+                        width.saturating_sub(console::measure_text_width(indent)),
+                        collapsed,
+                    )
+                    .into_iter()
+                    .map(|(line, comment)| (format!("{}{line}", style(indent).dim()), comment)),
+            );
+        }
+        lines
+    }
+
+    pub(crate) fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub(crate) fn short_id(&self) -> &str {
+        &self.short_id
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        self.content.trim()
     }
 
-    pub(crate) fn descendants_count(&self) -> usize {
+    /// Returns the number of replies to the comment, including replies to those.
+    fn count_replies(&self) -> usize {
         self.children
             .iter()
-            .map(|child| child.descendants_count())
-            .sum::<usize>()
-            + 1
+            .map(|child| 1 + child.count_replies())
+            .sum()
+    }
+
+    /// Returns this comment or the descendant of it with the short id `short_id`.
+    fn find(&self, short_id: &str) -> Option<&Comment> {
+        if self.short_id == short_id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(short_id))
     }
 }
 
-fn wrap(s: String, width: usize) -> String {
-    s.lines()
-        .map(|line| {
-            line.trim_end()
-                .chars()
-                .collect::<Vec<char>>()
-                .chunks(width)
-                .map(|chunk| chunk.iter().collect::<String>().trim().to_string())
-                .collect::<Vec<String>>()
-                .join("\n")
-        })
-        .collect::<Vec<String>>()
-        .join("\n")
+/// Parses the comments on a Hacker News item page into their threads.
+fn hacker_news_comments_from_html(html: &Html) -> Vec<Comment> {
+    let rows = Selector::parse("tr.athing.comtr").unwrap();
+    let mut threads = Vec::new();
+    // The comments the next comment may reply to, from the outermost to the innermost.
+    let mut open: Vec<Comment> = Vec::new();
+    let close = |open: &mut Vec<Comment>, threads: &mut Vec<Comment>| {
+        let comment = open.pop().unwrap();
+        match open.last_mut() {
+            Some(parent) => parent.children.push(comment),
+            None => threads.push(comment),
+        }
+    };
+    for (depth, comment) in html
+        .select(&rows)
+        .filter_map(Comment::from_hacker_news_html)
+    {
+        while open.len() > depth {
+            close(&mut open, &mut threads);
+        }
+        open.push(comment);
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut threads);
+    }
+    threads
 }
 
 pub(crate) fn prepend_string(s: &str, prefix: &str) -> String {
@@ -165,163 +302,742 @@ pub(crate) fn prepend_string(s: &str, prefix: &str) -> String {
 
 #[derive(Debug, Clone)]
 pub(crate) struct Story {
-    votes: usize,
+    /// The site the story is from.
+    source: Source,
+    /// The score of the story, if the source has scores.
+    votes: Option<usize>,
+    /// Whether the logged in user has upvoted the story.
+    upvoted: bool,
+    /// Whether the logged in user has flagged the story.
+    flagged: bool,
+    /// Whether the logged in user has hidden the story.
+    hidden: bool,
+    /// Whether the logged in user has saved the story.
+    saved: bool,
     title: String,
     description: bool,
+    /// The tags of the story, which are left empty by sources without tags.
     tags: Vec<Tag>,
     domain: Option<String>,
     byline: Byline,
     time: String,
-    comments_number: usize,
-    comments_url: String,
+    /// The number of comments, if the source tells.
+    comments_number: Option<usize>,
+    /// The url of the comment thread, if the story has one whose comments can be read.
+    comments_url: Option<String>,
     comments: Vec<Comment>,
+    /// Whether the comments have been loaded. The number of comments that can be loaded may differ
+    /// from `comments_number`, for example when some are deleted.
+    comments_loaded: bool,
     url: String,
+    /// Whether the story is listed on the merged front page, where it is marked with its sources.
+    merged: bool,
+    /// The same story on other sources of the merged front page, and other discussions of it that
+    /// were looked up, which can be switched to with [`Story::next_discussion`].
+    others: Vec<Story>,
+    /// Whether other discussions of the story have been looked up.
+    discussions_found: bool,
+    /// Why other discussions of the story could not be looked up, if they could not.
+    discussions_error: Option<String>,
 }
 
 impl Story {
-    pub(crate) fn from_html(html: ElementRef) -> Self {
+    /// Parses a story in a list of stories on lobste.rs. Returns `None` if it cannot be parsed.
+    pub(crate) fn from_html(html: ElementRef) -> Option<Self> {
+        Self::from_site_html(html, Source::Lobsters)
+    }
+
+    /// Parses a story in a list of stories on `source`, which is lobste.rs or another site that
+    /// runs the same software. Returns `None` if it cannot be parsed, for example because the
+    /// site changed its pages.
+    pub(crate) fn from_site_html(html: ElementRef, source: Source) -> Option<Self> {
+        let site_url = source.site_url().unwrap_or(base_url()).to_string();
         let s = |s| {
             html.select(&Selector::parse(s).unwrap())
-                .next()
-                .unwrap()
+                .next()?
                 .text()
                 .next()
         };
+        let attr = |selector, attr| {
+            html.select(&Selector::parse(selector).unwrap())
+                .next()?
+                .value()
+                .attr(attr)
+        };
 
         let description = html
             .select(&Selector::parse(".details > a.description_present").unwrap())
             .next()
             .is_some();
 
-        Self {
-            votes: usize::from_str(s(".voters > .score").unwrap()).unwrap(),
-            title: s(".details > .link > a").unwrap().to_string(),
+        // The vote or flag of the user is marked on the story element around the liner.
+        let marked = |mark| {
+            html.parent()
+                .and_then(ElementRef::wrap)
+                .is_some_and(|story| story.value().classes().any(|class| class == mark))
+        };
+
+        // The links to hide and save the story say 'unhide' and 'unsave' if the user already did.
+        let link_says = |selector, text| {
+            html.select(&Selector::parse(selector).unwrap())
+                .next()
+                .and_then(|a| a.text().next())
+                .is_some_and(|t| t.trim() == text)
+        };
+
+        Some(Self {
+            source,
+            votes: Some(usize::from_str(s(".voters > .score")?).ok()?),
+            upvoted: marked("upvoted"),
+            flagged: marked("flagged"),
+            hidden: link_says(".details > .byline a.hider", "unhide"),
+            saved: link_says(".details > .byline a.saver", "unsave"),
+            title: s(".details > .link > a")?.to_string(),
             description,
+            // Tags that are not known, such as those of other sites, are left out.
             tags: html
                 .select(&Selector::parse(".details > .tags > .tag").unwrap())
-                .map(|t| Tag::from_str(t.text().next().unwrap()).unwrap())
+                .filter_map(|t| Tag::from_str(t.text().next()?).ok())
                 .collect(),
             domain: {
                 let anchor = html
                     .select(&Selector::parse(".details > .domain").unwrap())
                     .next();
 
-                anchor.map(|s| s.text().next().unwrap().to_string())
+                anchor.and_then(|s| Some(s.text().next()?.to_string()))
             },
             byline: Byline::from_html(
                 html.select(&Selector::parse(".details > .byline").unwrap())
-                    .next()
-                    .unwrap(),
+                    .next()?,
             ),
             time: html
                 .select(&Selector::parse(".details > .byline > span").unwrap())
-                .skip(1)
-                .next()
-                .unwrap()
+                .nth(1)?
                 .text()
-                .next()
-                .unwrap()
+                .next()?
                 .to_string(),
-            comments_number: match s(".details > .byline > .comments_label > a")
-                .unwrap()
-                .split_whitespace()
-                .next()
-                .unwrap()
-            {
-                "no" => 0,
-                n => usize::from_str(n).unwrap(),
-            },
+            comments_number: Some(
+                match s(".details > .byline > .comments_label > a")?
+                    .split_whitespace()
+                    .next()?
+                {
+                    "no" => 0,
+                    n => usize::from_str(n).ok()?,
+                },
+            ),
             comments: Vec::new(),
+            comments_loaded: false,
             comments_url: {
-                let url = html
-                    .select(&Selector::parse(".details > .byline > .comments_label > a").unwrap())
-                    .next()
-                    .unwrap()
-                    .value()
-                    .attr("href")
-                    .unwrap()
-                    .to_owned();
-                format!("{URL}/{url}")
+                let url = attr(".details > .byline > .comments_label > a", "href")?;
+                Some(format!("{site_url}/{}", url.trim_start_matches('/')))
             },
             url: {
-                let url = html
-                    .select(&Selector::parse(".details > .link > a").unwrap())
-                    .next()
-                    .unwrap()
-                    .value()
-                    .attr("href")
-                    .unwrap()
-                    .to_owned();
+                let url = attr(".details > .link > a", "href")?;
                 if description {
-                    format!("{URL}/{url}")
+                    format!("{site_url}/{}", url.trim_start_matches('/'))
                 } else {
-                    url
+                    url.to_owned()
                 }
             },
+            merged: false,
+            others: Vec::new(),
+            discussions_found: false,
+            discussions_error: None,
+        })
+    }
+
+    /// Parses a story from its row in a list of stories on Hacker News. The score, submitter and
+    /// comments of the story are in the row that follows it. Returns `None` for rows that are
+    /// not a story.
+    pub(crate) fn from_hacker_news_html(row: ElementRef) -> Option<Self> {
+        let id = row.value().id()?;
+        let subtext = row.next_siblings().find_map(ElementRef::wrap)?;
+        let first = |element: ElementRef, selector| {
+            element
+                .select(&Selector::parse(selector).unwrap())
+                .next()
+                .map(|found| found.text().collect::<String>().trim().to_string())
+        };
+
+        let link = row
+            .select(&Selector::parse(".titleline > a").unwrap())
+            .next()?;
+        let href = link.value().attr("href")?;
+        // Posts without a link, such as Ask HN, link to their own comments.
+        let description = !href.contains("://");
+        let comments_url = format!("{}/item?id={id}", hacker_news_url());
+        // The link to the comments says 'discuss' or the number of comments. Job postings have
+        // neither.
+        let comments_number = subtext
+            .select(&Selector::parse("a").unwrap())
+            .map(|a| a.text().collect::<String>())
+            .find(|text| text.contains("comment"))
+            .and_then(|text| {
+                let number = text.split(|c: char| !c.is_ascii_digit()).next()?;
+                usize::from_str(number).ok()
+            })
+            .unwrap_or(0);
+
+        Some(Self {
+            source: Source::HackerNews,
+            votes: Some(
+                first(subtext, ".score")
+                    .and_then(|score| usize::from_str(score.split_whitespace().next()?).ok())
+                    .unwrap_or(0),
+            ),
+            upvoted: false,
+            flagged: false,
+            hidden: false,
+            saved: false,
+            title: link.text().collect::<String>().trim().to_string(),
+            description,
+            tags: Vec::new(),
+            domain: first(row, ".sitestr"),
+            byline: match first(subtext, ".hnuser") {
+                Some(user) => Byline::Via(user),
+                None => Byline::Unknown,
+            },
+            time: first(subtext, ".age").unwrap_or_default(),
+            comments_number: Some(comments_number),
+            url: if description {
+                comments_url.clone()
+            } else {
+                href.to_string()
+            },
+            comments_url: Some(comments_url),
+            comments: Vec::new(),
+            comments_loaded: false,
+            merged: false,
+            others: Vec::new(),
+            discussions_found: false,
+            discussions_error: None,
+        })
+    }
+
+    /// Makes a story of an item of the feed that is `source`. Comments are only available if the
+    /// item links to a comment thread on a source whose comments can be read.
+    pub(crate) fn from_feed_item(source: Source, item: FeedItem) -> Self {
+        let domain = Url::parse(&item.link).ok().and_then(|url| {
+            let host = url.host_str()?;
+            Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+        });
+        Self {
+            source,
+            votes: None,
+            upvoted: false,
+            flagged: false,
+            hidden: false,
+            saved: false,
+            title: item.title,
+            description: false,
+            tags: Vec::new(),
+            domain,
+            byline: match item.author {
+                Some(author) => Byline::Via(author),
+                None => Byline::Unknown,
+            },
+            time: item.time.unwrap_or_default(),
+            comments_number: None,
+            comments_url: item.comments.filter(|url| Source::of_thread(url).is_some()),
+            comments: Vec::new(),
+            comments_loaded: false,
+            url: item.link,
+            merged: false,
+            others: Vec::new(),
+            discussions_found: false,
+            discussions_error: None,
         }
     }
 
+    /// Makes a story of a story found with the search API of Hacker News. Returns `None` for
+    /// stories without a link, such as Ask HN posts.
+    pub(crate) fn from_hacker_news_hit(hit: HackerNewsHit) -> Option<Self> {
+        let url = hit.url.filter(|url| !url.is_empty())?;
+        let domain = Url::parse(&url).ok().and_then(|url| {
+            let host = url.host_str()?;
+            Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+        });
+        let time = hit
+            .created_at_i
+            .and_then(|created| time::OffsetDateTime::from_unix_timestamp(created).ok())
+            .map(|created| time_ago(created, time::OffsetDateTime::now_utc()));
+        Some(Self {
+            source: Source::HackerNews,
+            votes: hit.points,
+            upvoted: false,
+            flagged: false,
+            hidden: false,
+            saved: false,
+            title: hit.title?,
+            description: false,
+            tags: Vec::new(),
+            domain,
+            byline: match hit.author {
+                Some(author) => Byline::Via(author),
+                None => Byline::Unknown,
+            },
+            time: time.unwrap_or_default(),
+            comments_number: hit.num_comments,
+            comments_url: Some(format!("{}/item?id={}", hacker_news_url(), hit.id)),
+            comments: Vec::new(),
+            comments_loaded: false,
+            url,
+            merged: false,
+            others: Vec::new(),
+            discussions_found: false,
+            discussions_error: None,
+        })
+    }
+
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn comments_url(&self) -> Option<&str> {
+        self.comments_url.as_deref()
+    }
+
     pub(crate) fn url(&self) -> &String {
         &self.url
     }
 
+    pub(crate) fn byline(&self) -> &Byline {
+        &self.byline
+    }
+
+    pub(crate) fn votes(&self) -> Option<usize> {
+        self.votes
+    }
+
+    pub(crate) fn comments_number(&self) -> Option<usize> {
+        self.comments_number
+    }
+
+    /// Returns how long ago the story was submitted, such as "3 hours ago".
+    pub(crate) fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns how long ago the story was submitted in minutes, if that is known.
+    pub(crate) fn age_minutes(&self) -> Option<u64> {
+        minutes_ago(&self.time)
+    }
+
+    pub(crate) fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub(crate) fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub(crate) fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Marks the story as listed on the merged front page.
+    pub(crate) fn merged(self) -> Self {
+        Self {
+            merged: true,
+            ..self
+        }
+    }
+
+    /// Adds the same story from another source, whose discussion can then be switched to.
+    pub(crate) fn add_discussion(&mut self, other: Story) {
+        self.others.push(other);
+    }
+
+    /// Looks up other discussions of the page the story links to, and adds those that are not
+    /// known yet. They are only looked up once, and when that fails, the error is kept for
+    /// [`Story::discussions_error`] instead of looking them up again.
+    pub(crate) fn find_discussions(&mut self) {
+        // Stories without a link of their own link to their own discussion.
+        if self.discussions_found || Source::of_thread(&self.url).is_some() {
+            return;
+        }
+        self.discussions_found = true;
+        let found = match discussions::find(&self.url) {
+            Ok(found) => found,
+            Err(err) => {
+                self.discussions_error = Some(err);
+                return;
+            }
+        };
+        let mut known = std::iter::once(&*self)
+            .chain(&self.others)
+            .filter_map(|story| story.comments_url.as_deref().map(normalize_url))
+            .collect::<Vec<String>>();
+        for story in found {
+            let url = story.comments_url.as_deref().map(normalize_url);
+            if url.as_ref().is_some_and(|url| !known.contains(url)) {
+                known.extend(url);
+                self.others.push(story);
+            }
+        }
+    }
+
+    pub(crate) fn discussions_found(&self) -> bool {
+        self.discussions_found
+    }
+
+    /// Returns why other discussions of the story could not be looked up, if they could not.
+    pub(crate) fn discussions_error(&self) -> Option<&str> {
+        self.discussions_error.as_deref()
+    }
+
+    /// Returns the same story on the other sources of the merged front page.
+    pub(crate) fn others(&self) -> &[Story] {
+        &self.others
+    }
+
+    /// Switches to the discussion of the story on the next of its other sources. Returns whether
+    /// it has other sources.
+    pub(crate) fn next_discussion(&mut self) -> bool {
+        if self.others.is_empty() {
+            return false;
+        }
+        let mut others = std::mem::take(&mut self.others);
+        let next = others.remove(0);
+        let previous = std::mem::replace(self, next);
+        // Where the story is listed stays the same, and so do the discussions that were found.
+        self.merged = previous.merged;
+        self.discussions_found = previous.discussions_found;
+        self.discussions_error = previous.discussions_error.clone();
+        others.push(previous);
+        self.others = others;
+        true
+    }
+
+    /// Returns the short id of the story, which identifies it on the site.
+    fn short_id(&self) -> Option<&str> {
+        // Comment urls look like `https://lobste.rs/s/<short id>/<title>`.
+        self.comments_url
+            .as_deref()?
+            .split("/s/")
+            .nth(1)?
+            .split('/')
+            .next()
+    }
+
+    /// Returns the action that switches `toggle` for this story.
+    pub(crate) fn toggle_action(&self, toggle: StoryToggle) -> StoryAction {
+        match toggle {
+            StoryToggle::Vote if self.upvoted => StoryAction::Unvote,
+            StoryToggle::Vote => StoryAction::Upvote,
+            StoryToggle::Hide if self.hidden => StoryAction::Unhide,
+            StoryToggle::Hide => StoryAction::Hide,
+            StoryToggle::Save if self.saved => StoryAction::Unsave,
+            StoryToggle::Save => StoryAction::Save,
+        }
+    }
+
+    /// Returns whether the logged in user has flagged the story.
+    pub(crate) fn flagged(&self) -> bool {
+        self.flagged
+    }
+
+    /// Shows the outcome of `action` on the story, without telling the site.
+    ///
+    /// A story is either upvoted or flagged, and the score counts flags against upvotes.
+    pub(crate) fn apply(&mut self, action: StoryAction) {
+        let mut change_votes = |up: bool| {
+            self.votes = self.votes.map(|votes| match up {
+                true => votes + 1,
+                false => votes.saturating_sub(1),
+            })
+        };
+        match action {
+            StoryAction::Upvote if !self.upvoted => {
+                if self.flagged {
+                    change_votes(true);
+                }
+                change_votes(true);
+                self.flagged = false;
+                self.upvoted = true;
+            }
+            StoryAction::Unvote if self.upvoted => {
+                change_votes(false);
+                self.upvoted = false;
+            }
+            StoryAction::Flag(_) if !self.flagged => {
+                if self.upvoted {
+                    change_votes(false);
+                }
+                change_votes(false);
+                self.upvoted = false;
+                self.flagged = true;
+            }
+            StoryAction::Unflag if self.flagged => {
+                change_votes(true);
+                self.flagged = false;
+            }
+            StoryAction::Hide => self.hidden = true,
+            StoryAction::Unhide => self.hidden = false,
+            StoryAction::Save => self.saved = true,
+            StoryAction::Unsave => self.saved = false,
+            _ => {}
+        }
+    }
+
+    /// Returns the source of the comment thread of the story, if its comments can be read. For
+    /// a story from a feed, that is the site its thread is on, if any.
+    fn comments_source(&self) -> Option<Source> {
+        let url = self.comments_url.as_deref()?;
+        match &self.source {
+            Source::Feed(_) => Source::of_thread(url),
+            source => Some(source.clone()),
+        }
+    }
+
+    /// Returns whether the story has comments that can be read.
+    pub(crate) fn comments_available(&self) -> bool {
+        self.comments_source().is_some()
+    }
+
+    /// Returns an error if the logged in user cannot do things to the story, because it is not
+    /// on lobste.rs.
+    fn check_actions(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.comments_source() {
+            Some(Source::Lobsters) => Ok(()),
+            _ => Err(format!("this cannot be done on {}", self.source).into()),
+        }
+    }
+
+    /// Does `action` on the site.
+    pub(crate) fn perform(&self, action: StoryAction) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_actions()?;
+        let short_id = self.short_id().ok_or("the story has no id")?;
+        let form = match action {
+            StoryAction::Flag(reason) => vec![("reason", reason.code())],
+            _ => Vec::new(),
+        };
+        post_form(
+            format!("{}/stories/{short_id}/{}", base_url(), action.as_str()),
+            &form,
+        )?;
+        Ok(())
+    }
+
     pub(crate) fn load_comments(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // In case the comments have already been loaded, just return.
-        if self.comments_number == self.comments.len() {
+        if self.comments_loaded {
             return Ok(());
         }
-        let html = get_page(self.comments_url.clone())?;
-        let comments_selector =
-            Selector::parse("#inside > ol.comments > li.comments_subtree").unwrap();
-        let comments_list = html.select(&comments_selector).skip(1);
-        let comments = comments_list.map(Comment::from_html).collect();
-        self.comments = comments;
+        let (url, source) = match (&self.comments_url, self.comments_source()) {
+            (Some(url), Some(source)) => (url.clone(), source),
+            _ => return Ok(()),
+        };
+        let html = get_page(url)?;
+        self.comments = match source {
+            Source::HackerNews => hacker_news_comments_from_html(&html),
+            _ => comments_from_html(&html),
+        };
+        self.comments_loaded = true;
+        Ok(())
+    }
+
+    /// Loads the comment thread of the story again, to show the latest comments.
+    pub(crate) fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_actions()?;
+        let url = self
+            .comments_url
+            .clone()
+            .ok_or("the story has no comments")?;
+        let html = get_page(url)?;
+        if let Some(mut story) = Self::from_thread_html(&html) {
+            // The story stays where it was listed.
+            story.source = self.source.clone();
+            story.merged = self.merged;
+            story.others = std::mem::take(&mut self.others);
+            story.discussions_found = self.discussions_found;
+            story.discussions_error = self.discussions_error.take();
+            *self = story;
+        }
+        Ok(())
+    }
+
+    /// Posts a comment on the story, in reply to the comment with the short id `parent` if it is
+    /// given. The text is written in Markdown.
+    pub(crate) fn post_comment(
+        &self,
+        parent: Option<&str>,
+        text: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_actions()?;
+        let short_id = self.short_id().ok_or("the story has no id")?;
+        let mut form = vec![("story_id", short_id), ("comment", text)];
+        if let Some(parent) = parent {
+            form.push(("parent_comment_short_id", parent));
+        }
+        post_form(format!("{}/comments", base_url()), &form)?;
         Ok(())
     }
 
+    /// Returns the comment with the short id `short_id`, if it is loaded.
+    pub(crate) fn find_comment(&self, short_id: &str) -> Option<&Comment> {
+        self.comments
+            .iter()
+            .find_map(|comment| comment.find(short_id))
+    }
+
+    /// Parses the story of a comment thread page together with its comments.
+    pub(crate) fn from_thread_html(html: &Html) -> Option<Self> {
+        let story_selector = Selector::parse(".story > .story_liner").unwrap();
+        let mut story = Self::from_html(html.select(&story_selector).next()?)?;
+        story.comments = comments_from_html(html);
+        story.comments_loaded = true;
+        Some(story)
+    }
+
     pub(crate) fn comments(&self) -> &Vec<Comment> {
         &self.comments
     }
 
-    pub(crate) fn comments_descendants(&self) -> usize {
-        self.comments()
+    pub(crate) fn comments_loaded(&self) -> bool {
+        self.comments_loaded
+    }
+
+    /// Returns whether the story links to something other than a page, such as a PDF or a video,
+    /// going by its tags.
+    pub(crate) fn links_to_media(&self) -> bool {
+        self.tags
             .iter()
-            .map(|comment| comment.descendants_count())
-            .sum()
+            .any(|tag| matches!(tag, Tag::Audio | Tag::Pdf | Tag::Slides | Tag::Video))
     }
 
-    pub(crate) fn comments_number(&self) -> usize {
-        self.comments_number
+    /// Returns whether the title, domain, tags or author of this story match the search.
+    pub(crate) fn matches(&self, search: &Search) -> bool {
+        search.matches(&self.title)
+            || self.domain.as_deref().is_some_and(|d| search.matches(d))
+            || self
+                .tags
+                .iter()
+                .any(|t| search.matches_escaped(&t.to_string()))
+            || self.byline.user().is_some_and(|u| search.matches(u))
     }
 }
 
-fn wrap_escaped_to_lines(s: &str, max_width: u16) -> Vec<Vec<String>> {
-    let text_len = |s| console::strip_ansi_codes(s).len() as u16;
-    let words = s.split_whitespace();
-    let mut width = 0;
-    let mut lines: Vec<Vec<String>> = vec![vec![]];
-    for word in words {
-        width += text_len(word) + 1;
-        if width < max_width + 1 {
-            // Stil fits within the line.
-            lines.last_mut().unwrap().push(word.to_string());
-        } else {
-            // Word will not fit anymore.
-            lines.push(vec![word.to_string()]);
-            width = text_len(word) + 1;
+/// The things about a story the logged in user can switch on and off.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StoryToggle {
+    Vote,
+    Hide,
+    Save,
+}
+
+/// Something the logged in user can do to a story on the site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StoryAction {
+    Upvote,
+    Unvote,
+    Flag(FlagReason),
+    Unflag,
+    Hide,
+    Unhide,
+    Save,
+    Unsave,
+}
+
+impl StoryAction {
+    /// The name of the action in the url of the site.
+    fn as_str(&self) -> &'static str {
+        match self {
+            StoryAction::Upvote => "upvote",
+            StoryAction::Unvote => "unvote",
+            StoryAction::Flag(_) => "flag",
+            // A flag is a vote against the story, which is taken back like an upvote.
+            StoryAction::Unflag => "unvote",
+            StoryAction::Hide => "hide",
+            StoryAction::Unhide => "unhide",
+            StoryAction::Save => "save",
+            StoryAction::Unsave => "unsave",
         }
     }
+}
 
-    lines
+/// The reasons a story can be flagged for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FlagReason {
+    OffTopic,
+    AlreadyPosted,
+    BrokenLink,
+    Spam,
 }
 
-fn wrap_with_indent(s: &str, max_width: u16, indent: u16) -> String {
-    let spacer = " ".repeat(indent as usize);
-    wrap_escaped_to_lines(s, max_width - indent)
-        .iter()
-        .map(|l| l.join(" "))
-        .collect::<Vec<String>>()
-        .join(&format!("\n{spacer}"))
+impl FlagReason {
+    /// The prompt that asks for the reason of a flag, with the keys of the reasons in brackets.
+    pub(crate) const PROMPT: &'static str =
+        "flag as (o)ff-topic, (a)lready posted, (b)roken link or (s)pam: ";
+
+    /// Parses the answer to [`FlagReason::PROMPT`], which is the key of a reason or its name.
+    /// Returns `None` for an empty answer.
+    pub(crate) fn parse(input: &str) -> Result<Option<Self>, String> {
+        let reason = match input.trim().to_lowercase().as_str() {
+            "" => return Ok(None),
+            "o" | "off-topic" => FlagReason::OffTopic,
+            "a" | "already posted" => FlagReason::AlreadyPosted,
+            "b" | "broken link" => FlagReason::BrokenLink,
+            "s" | "spam" => FlagReason::Spam,
+            other => return Err(format!("{other:?} is not a reason to flag a story")),
+        };
+        Ok(Some(reason))
+    }
+
+    /// The code of the reason on the site.
+    fn code(&self) -> &'static str {
+        match self {
+            FlagReason::OffTopic => "O",
+            FlagReason::AlreadyPosted => "A",
+            FlagReason::BrokenLink => "B",
+            FlagReason::Spam => "S",
+        }
+    }
+}
+
+/// Reads how long ago something happened from a description such as "3 hours ago", in minutes.
+fn minutes_ago(time: &str) -> Option<u64> {
+    let mut words = time.split_whitespace();
+    let number = words.next()?;
+    let number = match number {
+        "a" | "an" => 1,
+        number => number.parse::<u64>().ok()?,
+    };
+    let unit = words.next()?.trim_end_matches('s');
+    let minutes = match unit {
+        "second" => 0,
+        "minute" => 1,
+        "hour" => 60,
+        "day" => 60 * 24,
+        "week" => 60 * 24 * 7,
+        "month" => 60 * 24 * 30,
+        "year" => 60 * 24 * 365,
+        _ => return None,
+    };
+    Some(number * minutes)
+}
+
+/// Parses the comments of a comment thread page.
+fn comments_from_html(html: &Html) -> Vec<Comment> {
+    let comments_selector = Selector::parse("#inside > ol.comments > li.comments_subtree").unwrap();
+    let comments_list = html.select(&comments_selector).skip(1);
+    comments_list.map(Comment::from_html).collect()
+}
+
+/// Renders the single line that takes the place of a muted story in the list, which tells `why` it
+/// is muted.
+pub(crate) fn display_muted_story(why: &str, columns: u16, selected: bool) -> String {
+    let mark = style("·".to_string());
+    let mark = if selected { mark.reverse() } else { mark };
+    let line = style(format!("muted story {why} (e to show)"))
+        .dim()
+        .italic()
+        .to_string();
+    format!(
+        "{}{}",
+        console::pad_str(&mark.to_string(), 5, console::Alignment::Center, None),
+        console::truncate_str(&line, (columns as usize).saturating_sub(5), "…")
+    )
 }
 
 pub(crate) fn display_story(story: &Story, columns: u16, selected: bool) -> String {
@@ -330,33 +1046,68 @@ pub(crate) fn display_story(story: &Story, columns: u16, selected: bool) -> Stri
     let tags = story
         .tags
         .iter()
-        .map(|t| format!("{}", style(t.to_string()).color256(94)))
+        .map(|t| words::highlight(&style(t.to_string()).color256(94).to_string()))
         .collect::<Vec<String>>()
         .join(" ");
-    let upper = format!(
-        "{title} {description} {tags}  {domain}",
-        title = style(&story.title).bold(),
-        description = if story.description { "☶ " } else { "" },
-        domain = style(match &story.domain {
-            Some(d) => d,
-            None => "",
-        })
-        .italic()
-        .dim()
-    );
-    let upper = wrap_with_indent(&upper, columns, 3 + 2);
-    let lower = format!(
-        "{} {} | {} comment{}",
-        story.byline,
-        story.time,
-        story.comments_number,
-        if story.comments_number == 1 { "" } else { "s" }
-    );
-    let lower = style(wrap_with_indent(&lower, columns, 3 + 2)).dim();
+    let mut upper = style(words::highlight(&story.title)).bold().to_string();
+    if story.description {
+        upper.push_str(" ☶");
+    }
+    // Sources without tags leave them out, rather than leaving a gap.
+    if !tags.is_empty() {
+        upper.push(' ');
+        upper.push_str(&tags);
+    }
+    if let Some(domain) = &story.domain {
+        upper.push_str(&format!("  {}", style(domain).italic().dim()));
+    }
+    let upper = wrap_hanging(&upper, columns as usize, 3 + 2).join("\n");
+    let mut lower = format!("{} {}", story.byline, story.time)
+        .trim()
+        .to_string();
+    match story.comments_number {
+        Some(number) => lower.push_str(&format!(
+            " | {number} comment{}",
+            if number == 1 { "" } else { "s" }
+        )),
+        None if story.comments_url.is_some() => lower.push_str(" | comments"),
+        None => {}
+    }
+    if story.saved {
+        lower.push_str(" | saved");
+    }
+    if story.hidden {
+        lower.push_str(" | hidden");
+    }
+    if story.flagged {
+        lower.push_str(" | flagged");
+    }
+    let mut lower = style(lower).dim().to_string();
+    // On the merged front page, the story is marked with every source it is on, starting with the
+    // one whose discussion is shown.
+    if story.merged {
+        let badges = std::iter::once(story)
+            .chain(&story.others)
+            .map(|story| {
+                style(format!("[{}]", story.source.badge()))
+                    .cyan()
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        lower = format!("{badges} {lower}");
+    }
+    let lower = wrap_hanging(&lower, columns as usize, 3 + 2).join("\n");
+    let votes = match story.votes {
+        Some(votes) if story.upvoted => style(format!("▲{votes}")).red(),
+        Some(votes) => style(votes.to_string()),
+        // Sources without scores still get a mark to show the selection on.
+        None => style("·".to_string()),
+    };
     let votes = if selected {
-        style(story.votes).reverse().to_string()
+        votes.reverse().to_string()
     } else {
-        story.votes.to_string()
+        votes.to_string()
     };
     format!(
         "{}{upper}\n{:>3}  {lower}",
@@ -364,3 +1115,225 @@ pub(crate) fn display_story(story: &Story, columns: u16, selected: bool) -> Stri
         " "
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::{Listing, SiteSearch, What};
+    use crate::testing::{fixture, stand_in};
+
+    /// Gets the stories of a search, from a saved search page.
+    fn search_stories() -> Vec<Story> {
+        let search = SiteSearch::parse("rust order:newest", What::Stories).unwrap();
+        stand_in().serve_page(
+            "/search?q=rust&what=stories&order=newest&page=1",
+            &fixture("search_stories.html"),
+        );
+        Source::Lobsters
+            .get_stories(&Listing::Search(search), 1)
+            .unwrap()
+    }
+
+    #[test]
+    fn stories_from_a_search_page() {
+        let stories = search_stories();
+        assert_eq!(stories.len(), 2);
+        let requests = stand_in().requests_to("/search");
+        assert!(requests.iter().all(|request| request.method == "GET"));
+
+        let story = &stories[0];
+        assert_eq!(story.title(), "Borrowing without tears");
+        assert_eq!(story.url(), "https://www.example.com/posts/borrowing");
+        assert_eq!(story.domain(), Some("example.com"));
+        let tags = story.tags().iter().map(Tag::name).collect::<Vec<_>>();
+        assert_eq!(tags, ["rust", "programming"]);
+        assert!(matches!(story.byline(), Byline::Via(user) if user == "alice"));
+        assert_eq!(story.time(), "2 hours ago");
+        assert_eq!(story.votes(), Some(25));
+        assert_eq!(story.comments_number(), Some(12));
+        assert_eq!(
+            story.comments_url(),
+            Some(stand_in().url("/s/abc123/borrowing_without_tears").as_str())
+        );
+    }
+
+    #[test]
+    fn text_story_from_a_search_page() {
+        let story = &search_stories()[1];
+        let thread = stand_in().url("/s/def456/what_are_you_doing_in_rust_this_week");
+        assert_eq!(story.title(), "What are you doing in Rust this week?");
+        // A story without a link of its own links to its thread.
+        assert_eq!(story.url(), &thread);
+        assert_eq!(story.domain(), None);
+        assert!(matches!(story.byline(), Byline::AuthoredBy(user) if user == "bob"));
+        assert_eq!(story.time(), "3 days ago");
+        assert_eq!(story.votes(), Some(3));
+        assert_eq!(story.comments_number(), Some(0));
+        assert_eq!(story.comments_url(), Some(thread.as_str()));
+    }
+
+    #[test]
+    fn flag_reasons() {
+        assert_eq!(FlagReason::parse(" O "), Ok(Some(FlagReason::OffTopic)));
+        assert_eq!(FlagReason::parse("spam"), Ok(Some(FlagReason::Spam)));
+        assert_eq!(FlagReason::parse(""), Ok(None));
+        assert!(FlagReason::parse("x").is_err());
+    }
+
+    #[test]
+    fn flagging_counts_against_an_upvote() {
+        let mut story = search_stories().remove(0);
+        story.apply(StoryAction::Upvote);
+        assert_eq!(story.votes(), Some(26));
+        story.apply(StoryAction::Flag(FlagReason::Spam));
+        assert!(story.flagged() && !story.upvoted);
+        assert_eq!(story.votes(), Some(24));
+        story.apply(StoryAction::Unflag);
+        assert!(!story.flagged());
+        assert_eq!(story.votes(), Some(25));
+    }
+
+    #[test]
+    fn flag_is_posted_with_its_reason() {
+        stand_in().serve_page("/stories/abc123/flag", "<html></html>");
+        let story = search_stories().remove(0);
+        story
+            .perform(StoryAction::Flag(FlagReason::AlreadyPosted))
+            .unwrap();
+        let requests = stand_in().requests_to("/stories/abc123/flag");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert!(requests[0].body.split('&').any(|field| field == "reason=A"));
+    }
+
+    /// Gets the stories of the front page of Hacker News, from a saved front page.
+    fn hacker_news_stories() -> Vec<Story> {
+        stand_in().serve_page("/hn/news?p=1", &fixture("hacker_news_front.html"));
+        Source::HackerNews.get_stories(&Listing::Front, 1).unwrap()
+    }
+
+    #[test]
+    fn stories_from_the_hacker_news_front_page() {
+        let stories = hacker_news_stories();
+        assert_eq!(stories.len(), 3);
+
+        let story = &stories[0];
+        assert_eq!(story.title(), "Writing a compiler in a weekend");
+        assert_eq!(story.url(), "https://www.example.org/compilers");
+        assert!(!story.description);
+        assert_eq!(story.domain(), Some("example.org"));
+        assert!(matches!(story.byline(), Byline::Via(user) if user == "hacker1"));
+        assert_eq!(story.time(), "3 hours ago");
+        assert_eq!(story.votes(), Some(128));
+        assert_eq!(story.comments_number(), Some(42));
+        let comments_url = stand_in().url("/hn/item?id=40000001");
+        assert_eq!(story.comments_url(), Some(comments_url.as_str()));
+        assert!(story.comments_available());
+
+        // A post without a link links to its own comments.
+        let ask = &stories[1];
+        assert_eq!(ask.title(), "Ask HN: How do you read long threads?");
+        assert!(ask.description);
+        assert_eq!(ask.url(), &stand_in().url("/hn/item?id=40000002"));
+        assert_eq!(ask.domain(), None);
+        assert_eq!((ask.votes(), ask.comments_number()), (Some(1), Some(0)));
+
+        // Job postings have no score, submitter or comments.
+        let job = &stories[2];
+        assert_eq!(job.title(), "Example Corp (YC W20) is hiring engineers");
+        assert!(matches!(job.byline(), Byline::Unknown));
+        assert_eq!(job.time(), "6 hours ago");
+        assert_eq!((job.votes(), job.comments_number()), (Some(0), Some(0)));
+    }
+
+    #[test]
+    fn rows_of_other_markup_are_not_stories() {
+        let html = Html::parse_document(
+            "<table><tr class=\"athing\"><td>No id</td></tr>\
+             <tr class=\"athing\" id=\"1\"><td class=\"title\">No link</td></tr>\
+             <tr><td class=\"subtext\"></td></tr>\
+             <tr class=\"athing\" id=\"2\"><td class=\"title\"><span class=\"titleline\">\
+             <a href=\"https://example.com\">No subtext</a></span></td></tr></table>",
+        );
+        let rows = Selector::parse("tr.athing").unwrap();
+        let stories = html
+            .select(&rows)
+            .filter_map(Story::from_hacker_news_html)
+            .collect::<Vec<_>>();
+        assert!(stories.is_empty());
+    }
+
+    #[test]
+    fn stories_that_cannot_be_parsed_are_left_out() {
+        // Neither a score that is not a number nor a byline without its user break the page.
+        let page = fixture("search_stories.html")
+            .replace(
+                r#"<div class="score">25</div>"#,
+                r#"<div class="score">~</div>"#,
+            )
+            .replace(r#"class="u-author h-card user_is_author""#, "");
+        stand_in();
+        let html = Html::parse_document(&page);
+        let stories_selector = Selector::parse("ol.stories > .story > .story_liner").unwrap();
+        let stories = html
+            .select(&stories_selector)
+            .filter_map(Story::from_html)
+            .collect::<Vec<_>>();
+        assert_eq!(stories.len(), 1);
+        assert_eq!(stories[0].title(), "What are you doing in Rust this week?");
+        assert!(matches!(stories[0].byline(), Byline::Unknown));
+    }
+
+    #[test]
+    fn comments_from_a_hacker_news_item_page() {
+        let mut story = hacker_news_stories().remove(0);
+        stand_in().serve_page("/hn/item?id=40000001", &fixture("hacker_news_item.html"));
+        story.load_comments().unwrap();
+        let comments = story.comments();
+        assert_eq!(comments.len(), 2);
+
+        let first = &comments[0];
+        assert_eq!(first.short_id(), "40000101");
+        assert_eq!(first.author(), "carol");
+        assert_eq!(first.time, "2 hours ago");
+        assert_eq!(
+            first.content,
+            "The parser was the easy part.\n\nType checking took the rest of the weekend."
+        );
+        assert_eq!(first.children.len(), 1);
+        let reply = &first.children[0];
+        assert_eq!(
+            (reply.author(), reply.content.as_str()),
+            ("dave", "It always is.")
+        );
+        // Deleted comments keep their place in the thread.
+        assert_eq!(reply.children.len(), 1);
+        let deleted = &reply.children[0];
+        assert_eq!(
+            (deleted.author(), deleted.content.as_str()),
+            ("[deleted]", "[deleted]")
+        );
+
+        assert_eq!(comments[1].author(), "erin");
+        assert!(comments[1].children.is_empty());
+    }
+
+    #[test]
+    fn a_failed_lookup_of_discussions_is_kept() {
+        let mut story = search_stories().remove(0);
+        // Nothing answers the search of Hacker News for this page.
+        story.url = "https://www.example.com/discussions/unreachable".to_string();
+        story.find_discussions();
+        assert!(story.discussions_found());
+        assert!(story.discussions_error().is_some());
+        assert!(story.others().is_empty());
+    }
+
+    #[test]
+    fn muted_story_fits_the_columns() {
+        let line = display_muted_story("from a-domain-with-a-long-name.example.com", 30, false);
+        assert_eq!(console::measure_text_width(&line), 30);
+        let line = display_muted_story("via bob", 80, true);
+        assert!(console::measure_text_width(&line) < 80);
+    }
+}
@@ -0,0 +1,301 @@
+//! The `submit` command, which submits a story to the site from the command line.
+
+use reqwest::Url;
+use scraper::Selector;
+use serde::Deserialize;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use crate::discussions::lobsters_submissions;
+use crate::http::{base_url, get_json, has_session, post_form_to_page};
+use crate::session::NOT_LOGGED_IN;
+use crate::Tag;
+
+const USAGE: &str = "usage: kreeftje submit <url> [--title <title>] [--tags <tag,tag,…>] \
+[--description <text>] [--author] [--dry-run]";
+
+/// A story to be submitted.
+#[derive(Debug, Default)]
+struct Submission {
+    url: String,
+    title: Option<String>,
+    tags: Vec<Tag>,
+    description: Option<String>,
+    /// Whether the user is the author of the story.
+    author: bool,
+    /// Whether to print the request instead of sending it.
+    dry_run: bool,
+}
+
+impl Submission {
+    fn parse(args: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut submission = Self::default();
+        let mut url = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value\n{USAGE}"));
+            match arg.as_str() {
+                "--title" => submission.title = Some(value()?.clone()),
+                "--tags" => submission.tags = parse_tags(value()?)?,
+                "--description" => submission.description = Some(value()?.clone()),
+                "--author" => submission.author = true,
+                "--dry-run" => submission.dry_run = true,
+                flag if flag.starts_with("--") => {
+                    return Err(format!("unknown option {flag}\n{USAGE}").into())
+                }
+                _ if url.is_none() => url = Some(arg.clone()),
+                _ => return Err(format!("unexpected argument {arg:?}\n{USAGE}").into()),
+            }
+        }
+        let url = url.ok_or(USAGE)?;
+        Url::parse(&url).map_err(|err| format!("invalid url {url:?}: {err}"))?;
+        submission.url = url;
+        Ok(submission)
+    }
+
+    /// The form the site expects for a new story.
+    fn form(&self) -> Vec<(&str, &str)> {
+        let mut form = vec![
+            ("story[url]", self.url.as_str()),
+            ("story[title]", self.title.as_deref().unwrap_or_default()),
+            (
+                "story[description]",
+                self.description.as_deref().unwrap_or_default(),
+            ),
+            ("story[user_is_author]", if self.author { "1" } else { "0" }),
+        ];
+        form.extend(self.tags.iter().map(|tag| ("story[tags][]", tag.name())));
+        form
+    }
+}
+
+/// Parses a list of tag names separated by commas or whitespace, checking that every tag exists.
+fn parse_tags(input: &str) -> Result<Vec<Tag>, String> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(Tag::from_str)
+        .collect()
+}
+
+/// The attributes of a url the site suggests for its submission.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct UrlAttributes {
+    title: Option<String>,
+}
+
+/// Gets the title the site suggests for a story with `url`.
+fn suggest_title(url: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let endpoint = Url::parse_with_params(
+        &format!("{}/stories/fetch_url_attributes", base_url()),
+        &[("fetch_url", url)],
+    )?;
+    let attributes: UrlAttributes = get_json(endpoint.to_string())?;
+    Ok(attributes.title.filter(|title| !title.trim().is_empty()))
+}
+
+/// Writes `prompt` to `output` and reads a line from `input`.
+fn prompt(
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    prompt: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    write!(output, "{prompt}")?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err("no input".into());
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Runs the `submit` command with the arguments that follow it.
+pub(crate) fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    submit(args, &mut std::io::stdin().lock(), &mut std::io::stdout())
+}
+
+/// Submits the story described by `args`, asking on `output` for what is missing and reading the
+/// answers from `input`.
+fn submit(
+    args: &[String],
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut submission = Submission::parse(args)?;
+    // Looking things up on the site requires logging in, but printing the request does not.
+    let logged_in = has_session();
+    if !logged_in && !submission.dry_run {
+        return Err(NOT_LOGGED_IN.into());
+    }
+
+    if logged_in {
+        let previous = lobsters_submissions(&submission.url)?;
+        if previous.is_empty() {
+            writeln!(output, "This url has not been submitted before.")?;
+        } else {
+            writeln!(output, "This url has been submitted before:")?;
+            for story in &previous {
+                writeln!(
+                    output,
+                    "  {} ({})",
+                    story.title(),
+                    story.comments_url().unwrap_or(story.url())
+                )?;
+            }
+        }
+    }
+
+    if submission.title.is_none() {
+        let suggestion = if logged_in {
+            suggest_title(&submission.url)?
+        } else {
+            None
+        };
+        let title = match &suggestion {
+            Some(suggestion) => {
+                let answer = prompt(input, output, &format!("title [{suggestion}]: "))?;
+                if answer.is_empty() {
+                    suggestion.clone()
+                } else {
+                    answer
+                }
+            }
+            None => prompt(input, output, "title: ")?,
+        };
+        if title.is_empty() {
+            return Err("a story needs a title".into());
+        }
+        submission.title = Some(title);
+    }
+
+    while submission.tags.is_empty() {
+        match parse_tags(&prompt(input, output, "tags: ")?) {
+            Ok(tags) if tags.is_empty() => writeln!(output, "A story needs at least one tag.")?,
+            Ok(tags) => submission.tags = tags,
+            Err(err) => writeln!(output, "{err}")?,
+        }
+    }
+
+    let url = format!("{}/stories", base_url());
+    if submission.dry_run {
+        writeln!(output, "POST {url}")?;
+        for (name, value) in submission.form() {
+            writeln!(output, "  {name} = {value:?}")?;
+        }
+        return Ok(());
+    }
+
+    let (story_url, html) = post_form_to_page(url.clone(), &submission.form())?;
+    // When the story is not accepted, the site shows the form again with the reasons.
+    if story_url.trim_end_matches('/') == url {
+        let selector = Selector::parse(".flash-error, #error_explanation li").unwrap();
+        let reasons = html
+            .select(&selector)
+            .map(|reason| reason.text().collect::<String>().trim().to_string())
+            .collect::<Vec<String>>();
+        return Err(format!("the story was not accepted: {}", reasons.join("; ")).into());
+    }
+    writeln!(output, "Submitted: {story_url}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{fixture, stand_in};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_arguments() {
+        let submission = Submission::parse(&args(&[
+            "https://example.com/",
+            "--tags",
+            "rust, programming",
+            "--author",
+        ]))
+        .unwrap();
+        assert_eq!(submission.url, "https://example.com/");
+        let tags = submission.tags.iter().map(Tag::name).collect::<Vec<_>>();
+        assert_eq!(tags, ["rust", "programming"]);
+        assert!(submission.author && !submission.dry_run);
+        assert!(Submission::parse(&args(&["not a url"])).is_err());
+        assert!(Submission::parse(&args(&["https://example.com/", "--tags", "nope"])).is_err());
+        assert!(Submission::parse(&args(&["https://example.com/", "--title"])).is_err());
+    }
+
+    #[test]
+    fn dry_run_checks_for_dupes_and_suggests_a_title() {
+        let url = "https://www.example.com/posts/borrowing";
+        // Where the CSRF token comes from when no other page has been loaded yet.
+        stand_in().serve_page("/settings", &fixture("user_profile.html"));
+        stand_in().serve_page("/stories/check_url_dupe", &fixture("check_url_dupe.html"));
+        stand_in().serve(
+            "/stories/fetch_url_attributes",
+            200,
+            "application/json",
+            r#"{"url": "https://www.example.com/posts/borrowing", "title": "Borrowing without tears"}"#,
+        );
+
+        // The suggested title is taken, and the tags are asked again after a wrong or empty answer.
+        let mut input = "\nnope\n\nrust\n".as_bytes();
+        let mut output = Vec::new();
+        submit(&args(&[url, "--dry-run"]), &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let thread = stand_in().url("/s/abc123/borrowing_without_tears");
+        let stories = stand_in().url("/stories");
+        assert_eq!(
+            output,
+            format!(
+                "This url has been submitted before:\n  Borrowing without tears ({thread})\n\
+                 title [Borrowing without tears]: tags: No tag with the name 'nope' exists\n\
+                 tags: A story needs at least one tag.\ntags: POST {stories}\n  \
+                 story[url] = \"{url}\"\n  story[title] = \"Borrowing without tears\"\n  \
+                 story[description] = \"\"\n  story[user_is_author] = \"0\"\n  \
+                 story[tags][] = \"rust\"\n"
+            )
+        );
+
+        let checks = stand_in().requests_to("/stories/check_url_dupe");
+        assert!(checks.iter().any(|check| check.method == "POST"
+            && check.body.contains("borrowing")
+            && check.headers.contains_key("cookie")));
+        let lookups = stand_in().requests_to("/stories/fetch_url_attributes");
+        assert!(lookups.iter().any(|lookup| lookup
+            .path
+            .contains("fetch_url=https%3A%2F%2Fwww.example.com")));
+        // Nothing is submitted on a dry run.
+        assert!(!stand_in()
+            .requests_to("/stories")
+            .iter()
+            .any(|request| request.body.contains("borrowing")));
+    }
+
+    #[test]
+    fn rejected_submission_is_an_error() {
+        // Where the CSRF token comes from when no other page has been loaded yet.
+        stand_in().serve_page("/settings", &fixture("user_profile.html"));
+        stand_in().serve_page("/stories/check_url_dupe", &fixture("check_url_dupe.html"));
+        stand_in().serve_page("/stories", &fixture("submit_rejected.html"));
+        let args = args(&[
+            "https://example.org/nothing",
+            "--title",
+            "Nothing",
+            "--tags",
+            "rust",
+        ]);
+        let err = submit(&args, &mut "".as_bytes(), &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the story was not accepted: Title cannot be blank.; \
+             Tags must contain at least one topical tag."
+        );
+        let posts = stand_in().requests_to("/stories");
+        assert!(posts
+            .iter()
+            .any(|post| post.method == "POST" && post.body.contains("story%5Btitle%5D=Nothing")));
+    }
+}
@@ -1,4 +1,5 @@
 use console::style;
+use std::fmt::Display;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy)]
@@ -368,10 +369,23 @@ impl FromStr for Tag {
     }
 }
 
-impl ToString for Tag {
-    fn to_string(&self) -> String {
+impl Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self.color() {
+            Color::Blue => 117,
+            Color::Red => 210,
+            Color::Magenta => 102,
+            Color::Yellow => 222,
+        };
+        write!(f, "{}", style(self.name()).color256(code))
+    }
+}
+
+impl Tag {
+    /// The name of the tag on the site.
+    pub(crate) fn name(&self) -> &'static str {
         use Tag::*;
-        let text = match self {
+        match self {
             Ai => "ai",
             Compsci => "compsci",
             Distributed => "distributed",
@@ -482,14 +496,7 @@ impl ToString for Tag {
             Systemd => "systemd",
             Vcs => "vcs",
             Vim => "vim",
-        };
-        let code = match self.color() {
-            Color::Blue => 117,
-            Color::Red => 210,
-            Color::Magenta => 102,
-            Color::Yellow => 222,
-        };
-        style(text).color256(code).to_string()
+        }
     }
 }
 
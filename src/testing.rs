@@ -0,0 +1,194 @@
+//! A stand-in for the sites the program talks to, and saved pages of them, for the tests.
+//!
+//! The stand-in is a small HTTP server on a local port, which answers the paths that the tests
+//! give it. The settings are set up once for all tests, with the site, Hacker News and the
+//! archives pointed at the stand-in, and with a session cookie.
+
+use scraper::Html;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{LazyLock, Mutex};
+
+use crate::config::Config;
+use crate::mutes::Mutes;
+use crate::settings::{self, Settings};
+
+/// The session cookie the tests are logged in with.
+pub(crate) const COOKIE: &str = "secret";
+
+static STAND_IN: LazyLock<StandIn> = LazyLock::new(StandIn::start);
+
+/// A request the stand-in received.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub(crate) method: String,
+    /// The path of the request, with the query.
+    pub(crate) path: String,
+    /// The headers of the request, with lowercase names.
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: String,
+}
+
+#[derive(Debug, Clone)]
+struct Response {
+    status: u16,
+    content_type: String,
+    body: String,
+}
+
+pub(crate) struct StandIn {
+    base_url: String,
+    /// The responses, by path. A path with a query is answered before the same path without one.
+    routes: Mutex<HashMap<String, Response>>,
+    requests: Mutex<Vec<Request>>,
+}
+
+/// Returns the stand-in, which is started and set up as the site in the settings on first use.
+pub(crate) fn stand_in() -> &'static StandIn {
+    &STAND_IN
+}
+
+impl StandIn {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let mut config = Config::default();
+        config.site.url = base_url.clone();
+        config.session.cookie = Some(COOKIE.to_string());
+        config.hacker_news.url = format!("{base_url}/hn");
+        config.archive.wayback_url = format!("{base_url}/wayback");
+        config.archive.archive_today_url = format!("{base_url}/today");
+        config.discussions.hacker_news = true;
+        config.discussions.hacker_news_search_url = format!("{base_url}/algolia");
+        config
+            .filters
+            .insert("popular".to_string(), "score>=20 -tag:ask".to_string());
+        config.words.mute = vec!["crypto".to_string()];
+        config.http.retries = 0;
+        config.http.min_interval_ms = 0;
+        config.http.timeout_secs = 5;
+        let settings = Settings::new(&config, Mutes::load(None).unwrap()).unwrap();
+        settings::set(settings).unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || STAND_IN.answer(stream));
+            }
+        });
+        Self {
+            base_url,
+            routes: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the url of `path` on the stand-in.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Returns the url of `path` on the stand-in under another host name, for the sites that are
+    /// not the site of the settings.
+    pub(crate) fn other_url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url.replace("127.0.0.1", "localhost"))
+    }
+
+    /// Answers requests for `path` with `body`, with the status `status`.
+    pub(crate) fn serve(&self, path: &str, status: u16, content_type: &str, body: &str) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Response {
+                status,
+                content_type: content_type.to_string(),
+                body: body.to_string(),
+            },
+        );
+    }
+
+    /// Answers requests for `path` with the page `body`.
+    pub(crate) fn serve_page(&self, path: &str, body: &str) {
+        self.serve(path, 200, "text/html; charset=utf-8", body);
+    }
+
+    /// Returns the requests for `path` so far, with or without a query.
+    pub(crate) fn requests_to(&self, path: &str) -> Vec<Request> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.path.split('?').next() == Some(path))
+            .cloned()
+            .collect()
+    }
+
+    fn answer(&self, stream: TcpStream) {
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() {
+            return;
+        }
+        let mut parts = line.split_whitespace();
+        let (method, path) = match (parts.next(), parts.next()) {
+            (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+            _ => return,
+        };
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+        let length = headers
+            .get("content-length")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        let _ = reader.read_exact(&mut body);
+
+        let response = {
+            let routes = self.routes.lock().unwrap();
+            routes
+                .get(&path)
+                .or_else(|| routes.get(path.split('?').next().unwrap_or_default()))
+                .cloned()
+        };
+        self.requests.lock().unwrap().push(Request {
+            method,
+            path,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        });
+        let response = response.unwrap_or(Response {
+            status: 404,
+            content_type: "text/plain".to_string(),
+            body: "not found".to_string(),
+        });
+        let _ = write!(
+            &stream,
+            "HTTP/1.1 {} STAND-IN\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.content_type,
+            response.body.len(),
+            response.body
+        );
+        let _ = (&stream).flush();
+    }
+}
+
+/// Returns the saved page `name` from `tests/fixtures`.
+pub(crate) fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("cannot read {path}: {err}"))
+}
+
+/// Parses the saved page `name` from `tests/fixtures`, with the stand-in set up as the site.
+pub(crate) fn document(name: &str) -> Html {
+    stand_in();
+    Html::parse_document(&fixture(name))
+}
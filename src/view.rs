@@ -1,140 +1,574 @@
-use crate::story::{display_story, prepend_string, Story};
-use crate::{get_stories, STORIES_PER_SITE_PAGE};
+use crate::archive::Archive;
+use crate::filter::Filter;
+use crate::http::{get_document, is_status_error};
+use crate::listing::Listing;
+use crate::mutes;
+use crate::pages::{Layout, Pages};
+use crate::reader::Article;
+use crate::search::Search;
+use crate::source::{self, Source};
+use crate::status::Status;
+use crate::story::{
+    display_muted_story, display_story, prepend_string, Comment, Story, StoryAction, StoryToggle,
+};
+use crate::words;
+use crate::wrap::{wrap, wrap_hanging};
+use console::style;
+use scraper::Html;
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// The most site pages that are loaded at once to fill the presented page, when the filter leaves
+/// out many stories.
+const MAX_PAGES_PER_LOAD: usize = 5;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ViewMode {
     List,
     Comments,
+    /// The article of the selected story, read in the terminal.
+    Reader,
+}
+
+/// An article that was loaded for the reader.
+struct ReadArticle {
+    /// The url of the story the article belongs to.
+    story_url: String,
+    /// The url the article was read from, which is that of an archived copy if `archive` is set.
+    url: String,
+    archive: Option<Archive>,
+    /// Why the page of the story could not be read, when the archived copy was read instead.
+    fallback_reason: Option<String>,
+    article: Article,
 }
 
 pub(crate) struct View {
+    /// The site the stories of the listing are read from.
+    source: Source,
+    listing: Listing,
+    /// All loaded stories, also those that the filter leaves out.
     stories: Vec<Story>,
-    list_pos: usize,
+    /// The filter that the listed stories match.
+    filter: Option<Filter>,
+    /// The indices in `stories` of the listed stories, which are those that match the filter and
+    /// have no mute word in the title. Positions in the list, such as the selection in `pages`, are
+    /// positions in this.
+    listed: Vec<usize>,
+    /// The number of site pages of the listing that have been loaded.
+    site_pages_loaded: usize,
+    /// Whether the last site page of the listing has been loaded.
+    exhausted: bool,
+    /// The error that occurred when loading stories or comments, until a retry is requested.
+    error: Option<String>,
+    /// An action on a story, by index, that is shown but has not been sent to the site yet,
+    /// together with the story as it was before.
+    pending: Option<(usize, StoryAction, Story)>,
+    /// When the first site page of the listing was loaded.
+    loaded_at: Option<Instant>,
+    /// The selected story and the presented page of the list.
+    pages: Pages,
     pub comments_pos: usize,
-    page_size: usize,
+    /// The urls of the muted stories and the short ids of the muted comments that are shown
+    /// anyway.
+    expanded: HashSet<String>,
+    /// The size of the area the view is drawn in, in columns and rows.
+    width: u16,
+    height: u16,
     mode: ViewMode,
+    search: Option<Search>,
+    /// Line numbers of the search hits in the most recently rendered comments.
+    comments_hits: Vec<usize>,
+    /// The short id of the comment each line belongs to in the most recently rendered comments.
+    comments_ids: Vec<String>,
+    /// The short id of a comment to scroll to when the comments are rendered next.
+    comments_focus: Option<String>,
+    /// The most recently read article.
+    article: Option<Box<ReadArticle>>,
+    /// The archive to read the copy of the story from instead of its page, if asked for.
+    reader_archive: Option<Archive>,
+    reader_pos: usize,
+    /// The number of lines of the most recently rendered article.
+    reader_lines: usize,
+    /// Line numbers of the search hits in the most recently rendered article.
+    reader_hits: Vec<usize>,
 }
 
 impl View {
-    /// Creates a new empty view.
-    pub(crate) fn new(page_size: usize) -> Self {
+    /// Creates a new empty view of the front page of the selected source.
+    pub(crate) fn new() -> Self {
         Self {
+            source: source::selected(),
+            ..Self::with_listing(Listing::Front)
+        }
+    }
+
+    /// Creates a new empty view of a listing on lobste.rs.
+    ///
+    /// The view has no size until [`View::resize`] is called.
+    pub(crate) fn with_listing(listing: Listing) -> Self {
+        Self {
+            source: Source::Lobsters,
+            listing,
             stories: Vec::new(),
-            list_pos: 0,
+            filter: None,
+            listed: Vec::new(),
+            site_pages_loaded: 0,
+            exhausted: false,
+            error: None,
+            pending: None,
+            loaded_at: None,
+            pages: Pages::default(),
             comments_pos: 0,
-            page_size,
+            expanded: HashSet::new(),
+            width: 0,
+            height: 0,
             mode: ViewMode::List,
+            search: None,
+            comments_hits: Vec::new(),
+            comments_ids: Vec::new(),
+            comments_focus: None,
+            article: None,
+            reader_archive: None,
+            reader_pos: 0,
+            reader_lines: 0,
+            reader_hits: Vec::new(),
         }
     }
 
-    /// Load the next page of stories according to the number of stories stored.
+    /// Creates a view of a single story of which the comments are already loaded, with the
+    /// comments opened.
+    pub(crate) fn thread(url: String, story: Story) -> Self {
+        let mut view = Self::with_listing(Listing::Thread(url));
+        view.stories.push(story);
+        view.listed.push(0);
+        view.site_pages_loaded = 1;
+        view.exhausted = true;
+        view.loaded_at = Some(Instant::now());
+        view.mode = ViewMode::Comments;
+        view
+    }
+
+    /// Load the next page of stories according to the number of site pages loaded.
     fn load_stories(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // The page numbers of the site are 1-indexed.
-        let stories = &mut get_stories((self.stories.len() / STORIES_PER_SITE_PAGE) as u16 + 1)?;
+        let stories = &mut self
+            .source
+            .get_stories(&self.listing, self.site_pages_loaded + 1)?;
+        if stories.is_empty() {
+            self.exhausted = true;
+        }
+        if self.site_pages_loaded == 0 {
+            self.loaded_at = Some(Instant::now());
+        }
+        self.site_pages_loaded += 1;
+        let first = self.stories.len();
         self.stories.append(stories);
+        self.list_matching(first);
         Ok(())
     }
 
-    /// Get new pages of stories until the number of stories stored exceeds `pos`.
+    /// Lists the stories from index `first` on that match the filter. Stories with a mute word in
+    /// the title are not listed at all.
+    fn list_matching(&mut self, first: usize) {
+        for idx in first..self.stories.len() {
+            let story = &self.stories[idx];
+            if words::muted_word(story.title()).is_none()
+                && self
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(story))
+            {
+                self.listed.push(idx);
+            }
+        }
+    }
+
+    /// Returns the number of listed stories.
+    fn len(&self) -> usize {
+        self.listed.len()
+    }
+
+    /// Get new pages of stories until the number of listed stories exceeds `pos`, or the listing
+    /// has no more stories.
+    ///
+    /// When the filter leaves out most stories, this could take many pages, so no more than
+    /// [`MAX_PAGES_PER_LOAD`] are loaded at once. The next ones are loaded when the view is drawn
+    /// again.
     fn load_stories_including(&mut self, pos: usize) -> Result<(), Box<dyn std::error::Error>> {
-        while self.stories.len() <= pos {
+        let mut pages = 0;
+        while self.len() <= pos && !self.exhausted && pages < MAX_PAGES_PER_LOAD {
             self.load_stories()?;
+            pages += 1;
         }
 
         Ok(())
     }
 
-    /// Get a new page of stories when pos steps onto the next presentation page and the current
-    /// list of stories does not extend far enough to fill that page.
-    pub(crate) fn load_stories_next_page(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // If the current stories list can accomodate another view page, do nothing. Otherwise,
-        // load new stories.
+    /// Sets the filter the listed stories must match, or clears it when `filter` is `None`. The
+    /// selected story stays selected if it matches.
+    pub(crate) fn set_filter(&mut self, filter: Option<Filter>) {
+        let selected = self.listed.get(self.pages.pos).copied();
+        self.filter = filter;
+        self.listed.clear();
+        self.list_matching(0);
+        self.pages = Pages {
+            pos: selected
+                .and_then(|selected| self.listed.iter().position(|&idx| idx == selected))
+                .unwrap_or(0),
+            start: 0,
+        };
+        self.comments_pos = 0;
+        self.fit_page();
+    }
 
-        if self.stories.len() < self.list_pos + self.page_size - (self.list_pos % self.page_size) {
-            self.load_stories_including(self.list_pos + STORIES_PER_SITE_PAGE - 1)?;
+    /// Loads what is needed to show the view: the stories on the presented page and, in the
+    /// comments view, the comments of the selected story.
+    ///
+    /// If that fails, the error is kept and shown in the status bar, and no further loading is
+    /// attempted until [`View::retry`] is called.
+    pub(crate) fn load(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(err) = self.load_stories_next_page() {
+            self.error = Some(err.to_string());
+            return;
         }
+        match self.mode {
+            ViewMode::List => {}
+            ViewMode::Comments => {
+                if let Some(story) = self.get_selected_story_mut() {
+                    let mut result = Ok(());
+                    if !story.comments_loaded() {
+                        result = story.load_comments();
+                    }
+                    // Other discussions are only looked up once the comments are there. When that
+                    // fails, the comments are still shown, with the error above them.
+                    if result.is_ok() && !story.discussions_found() {
+                        story.find_discussions();
+                    }
+                    if let Err(err) = result {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            }
+            ViewMode::Reader => {
+                if let Err(err) = self.load_article() {
+                    self.error = Some(err.to_string());
+                }
+            }
+        }
+    }
 
+    /// Loads the article of the selected story for the reader, if it is not loaded yet.
+    ///
+    /// When the site of the story responds with an error, such as for a page that is gone, the
+    /// archived copy is read instead. Stories that link to something that cannot be read in the
+    /// terminal, such as a PDF or a video, are opened in the browser instead, and the view goes
+    /// back to the list.
+    fn load_article(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let story = match self.get_selected_story() {
+            Some(story) => story,
+            None => return Ok(()),
+        };
+        let url = story.url().clone();
+        if self.article.as_ref().is_some_and(|read| {
+            read.story_url == url
+                && (self.reader_archive.is_none() || read.archive == self.reader_archive)
+        }) {
+            return Ok(());
+        }
+        if self.reader_archive.is_none() && story.links_to_media() {
+            self.mode = ViewMode::List;
+            webbrowser::open(&url)?;
+            return Ok(());
+        }
+
+        let (archive, fallback_reason, (read_url, html)) = match self.reader_archive {
+            Some(archive) => (Some(archive), None, read_page(&url, Some(archive))?),
+            None => read_page_or_archived(&url)?,
+        };
+        match html {
+            Some(html) => {
+                self.article = Some(Box::new(ReadArticle {
+                    story_url: url,
+                    url: read_url,
+                    archive,
+                    fallback_reason,
+                    article: Article::from_html(&html),
+                }));
+                self.reader_pos = 0;
+            }
+            None => {
+                self.mode = ViewMode::List;
+                webbrowser::open(&read_url)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the copy of the selected story from `archive` in the reader, or opens it in the
+    /// browser when the reader is not open.
+    pub(crate) fn open_archived(
+        &mut self,
+        archive: Archive,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.mode {
+            ViewMode::Reader => self.reader_archive = Some(archive),
+            ViewMode::List | ViewMode::Comments => {
+                if let Some(story) = self.get_selected_story() {
+                    webbrowser::open(&archive.url(story.url()))?;
+                }
+            }
+        }
         Ok(())
     }
 
-    fn paginate(&mut self) -> Vec<(bool, Story)> {
-        let mut chunks = self.stories.chunks(self.page_size);
-        let chunk = chunks.nth(self.view_page()).unwrap();
-        chunk
-            .iter()
-            .enumerate()
-            .map(|(idx, story)| (idx == self.list_pos % self.page_size, story.to_owned()))
+    /// Allows loading to be attempted again after an error.
+    pub(crate) fn retry(&mut self) {
+        self.error = None;
+    }
+
+    /// Get a new page of stories when the presented page may extend beyond the current list of
+    /// stories.
+    fn load_stories_next_page(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Every story takes up at least one line, as a muted story does, so no more than the
+        // height in stories can fit on the page. If the current stories list can accomodate that,
+        // do nothing. Otherwise, load new stories.
+        let max_page_end = self.pages.start.max(self.pages.pos) + self.height as usize;
+        if self.len() <= max_page_end {
+            self.load_stories_including(max_page_end)?;
+        }
+        // A listing may run out of stories before the position is reached.
+        self.pages.pos = self.pages.pos.min(self.len().saturating_sub(1));
+        self.fit_page();
+
+        Ok(())
+    }
+
+    /// Sets the size of the area the view is drawn in, and moves the page such that the selected
+    /// story stays on it.
+    pub(crate) fn resize(&mut self, width: u16, height: u16) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.fit_page();
+        }
+    }
+
+    /// Renders a story in the list, or the line in its place if it is muted and not shown anyway.
+    fn display(&self, story: &Story, columns: u16, selected: bool) -> String {
+        match mutes::story_muted(story) {
+            Some(why) if !self.expanded.contains(story.url()) => {
+                display_muted_story(&why, columns, selected)
+            }
+            _ => display_story(story, columns, selected),
+        }
+    }
+
+    /// The number of lines a story takes up in the list.
+    fn story_height(&self, story: &Story) -> usize {
+        self.display(story, self.width.saturating_sub(3), false)
+            .lines()
+            .count()
+    }
+
+    /// How the listed stories take up the lines of the view.
+    fn layout(&self) -> Layout<'_> {
+        Layout {
+            len: self.len(),
+            height: self.height as usize,
+            entry_height: Box::new(|pos| self.get_story(pos).map_or(0, |s| self.story_height(s))),
+        }
+    }
+
+    /// Moves the presented page such that the selected story is on it.
+    fn fit_page(&mut self) {
+        let mut pages = self.pages;
+        pages.fit(&self.layout());
+        self.pages = pages;
+    }
+
+    fn paginate(&self) -> Vec<(bool, &Story)> {
+        self.pages
+            .page(&self.layout())
+            .filter_map(|pos| Some((pos == self.pages.pos, self.get_story(pos)?)))
             .collect()
     }
 
     pub(crate) fn go_to(&mut self, travel: Travel) {
         match self.mode {
             ViewMode::List => {
-                match travel {
-                    Travel::NextStep => self.list_pos += self.page_size,
-                    Travel::PrevStep => self.list_pos -= self.list_pos.min(self.page_size),
-                    Travel::NextItem => self.list_pos += 1,
-                    Travel::PrevItem => self.list_pos -= self.list_pos.min(1),
-                    Travel::Top => self.list_pos = 0,
-                    Travel::Bottom => self.list_pos = self.stories.len() - 1,
-                }
+                let mut pages = self.pages;
+                pages.go_to(travel, &self.layout());
+                self.pages = pages;
                 // Reset the comments_pos every time a movement occurs in the List ViewMode.
                 self.comments_pos = 0;
             }
             ViewMode::Comments => match travel {
-                Travel::NextStep => self.list_pos += 1,
-                Travel::PrevStep => self.list_pos -= self.list_pos.min(1),
+                Travel::NextStep => self.pages.pos += 1,
+                Travel::PrevStep => self.pages.pos -= self.pages.pos.min(1),
                 Travel::NextItem => self.comments_pos += 1,
                 Travel::PrevItem => self.comments_pos -= self.comments_pos.min(1),
                 Travel::Top => self.comments_pos = 0,
-                // HACK: This is a very lousy way of traveling to the bottom of the comments view.
-                Travel::Bottom => self.comments_pos = 1_000_000,
+                // The last page of the comments as they were rendered last.
+                Travel::Bottom => {
+                    self.comments_pos = self.comments_ids.len().saturating_sub(self.height as usize)
+                }
+            },
+            ViewMode::Reader => match travel {
+                Travel::NextStep => {
+                    self.pages.pos += 1;
+                    self.reader_archive = None;
+                }
+                Travel::PrevStep => {
+                    self.pages.pos -= self.pages.pos.min(1);
+                    self.reader_archive = None;
+                }
+                Travel::NextItem => self.reader_pos += 1,
+                Travel::PrevItem => self.reader_pos -= self.reader_pos.min(1),
+                Travel::Top => self.reader_pos = 0,
+                Travel::Bottom => {
+                    self.reader_pos = self.reader_lines.saturating_sub(self.height as usize)
+                }
             },
         }
     }
 
     fn get_story(&self, pos: usize) -> Option<&Story> {
-        self.stories.get(pos)
+        self.stories.get(*self.listed.get(pos)?)
     }
 
     fn get_story_mut(&mut self, pos: usize) -> Option<&mut Story> {
-        self.stories.get_mut(pos)
+        self.stories.get_mut(*self.listed.get(pos)?)
+    }
+
+    pub(crate) fn get_selected_story(&self) -> Option<&Story> {
+        self.get_story(self.pages.pos)
     }
 
-    pub(crate) fn get_selected_story(&self) -> &Story {
-        self.get_story(self.list_pos).unwrap()
+    pub(crate) fn get_selected_story_mut(&mut self) -> Option<&mut Story> {
+        self.get_story_mut(self.pages.pos)
     }
 
-    pub(crate) fn get_selected_story_mut(&mut self) -> &mut Story {
-        self.get_story_mut(self.list_pos).unwrap()
+    /// Switches `toggle` for the selected story, like [`View::act`].
+    pub(crate) fn toggle(&mut self, toggle: StoryToggle) {
+        if let Some(story) = self.get_selected_story() {
+            let action = story.toggle_action(toggle);
+            self.act(action);
+        }
+    }
+
+    /// Does `action` to the selected story. The outcome is shown right away, and the action is
+    /// sent to the site by [`View::perform_pending`].
+    pub(crate) fn act(&mut self, action: StoryAction) {
+        let pos = self.pages.pos;
+        if let Some(story) = self.get_story_mut(pos) {
+            let before = story.clone();
+            story.apply(action);
+            self.pending = Some((pos, action, before));
+        }
+    }
+
+    /// Sends the pending action to the site, if there is one. If that fails, the story is shown as
+    /// it was before. Returns whether there was an action to send.
+    pub(crate) fn perform_pending(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        let (pos, action, before) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(false),
+        };
+        let story = match self.get_story_mut(pos) {
+            Some(story) => story,
+            None => return Ok(true),
+        };
+        if let Err(err) = story.perform(action) {
+            *story = before;
+            return Err(err);
+        }
+        Ok(true)
     }
 
-    pub(crate) fn generate_string(&mut self, width: u16, height: u16) -> String {
+    pub(crate) fn generate_string(&mut self) -> String {
+        let (width, height) = (self.width, self.height);
         match self.mode {
             ViewMode::List => {
                 let current_stories_page = self.paginate();
-                let displayed_stories = current_stories_page
-                    .into_iter()
-                    .map(|(selected, story)| display_story(&story, width - 3, selected));
-                displayed_stories.collect::<Vec<String>>().join("\n")
+                if current_stories_page.is_empty() {
+                    return match &self.filter {
+                        Some(_) if self.exhausted => "No stories match the filter.".to_string(),
+                        Some(_) => {
+                            "No stories match the filter yet, press j to look further.".to_string()
+                        }
+                        None => "No stories.".to_string(),
+                    };
+                }
+                let displayed_stories =
+                    current_stories_page.into_iter().map(|(selected, story)| {
+                        self.display(story, width.saturating_sub(3), selected)
+                    });
+                let list = displayed_stories.collect::<Vec<String>>().join("\n");
+                match &self.search {
+                    Some(search) => list
+                        .lines()
+                        .map(|line| search.highlight(line))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                    None => list,
+                }
             }
             ViewMode::Comments => {
                 let margin = 2;
-                let comments = self.get_selected_story().comments();
+                let story = match self.get_selected_story() {
+                    Some(story) => story,
+                    None => return "No story selected.".to_string(),
+                };
+                let (comments, available) = (story.comments(), story.comments_available());
+                let header = comments_header(story, (width as usize).saturating_sub(margin * 2));
                 if !comments.is_empty() {
-                    let lines = comments
-                        .iter()
-                        .map(|comment| {
-                            prepend_string(
-                                &comment.to_string(width as usize - (margin * 2)),
-                                &" ".repeat(margin),
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join("\n")
-                        .lines()
-                        .map(|line| line.to_string())
-                        .collect::<Vec<String>>();
+                    let mut lines = Vec::new();
+                    let mut ids = Vec::new();
+                    // The header does not belong to a comment.
+                    for line in header {
+                        lines.push(prepend_string(&line, &" ".repeat(margin)));
+                        ids.push(String::new());
+                    }
+                    // Muted comments are collapsed unless they are shown anyway.
+                    let collapsed = |comment: &Comment| {
+                        mutes::comment_muted(comment) && !self.expanded.contains(comment.short_id())
+                    };
+                    for (idx, comment) in comments.iter().enumerate() {
+                        // Separate the top-level comments by an empty line.
+                        if idx > 0 {
+                            lines.push(String::new());
+                            ids.push(comment.short_id().to_string());
+                        }
+                        for (line, owner) in comment
+                            .to_lines((width as usize).saturating_sub(margin * 2), &collapsed)
+                        {
+                            let line = prepend_string(&line, &" ".repeat(margin));
+                            lines.push(match &self.search {
+                                Some(search) => search.highlight(&line),
+                                None => line,
+                            });
+                            ids.push(owner.short_id().to_string());
+                        }
+                    }
+                    self.comments_ids = ids;
+                    if let Some(focus) = self.comments_focus.take() {
+                        if let Some(line) = self.comments_ids.iter().position(|id| *id == focus) {
+                            self.comments_pos = line;
+                        }
+                    }
+
+                    self.comments_hits = match &self.search {
+                        Some(search) => lines
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, line)| search.matches_escaped(line))
+                            .map(|(idx, _)| idx)
+                            .collect(),
+                        None => Vec::new(),
+                    };
 
                     if lines.len() > height as usize {
                         self.comments_pos =
@@ -143,21 +577,187 @@ impl View {
                         let end = lines.len().min(self.comments_pos + height as usize);
                         lines[start..end].join("\n")
                     } else {
+                        self.comments_pos = 0;
                         lines.join("\n")
                     }
                 } else {
-                    "No comments, yet.".to_string()
+                    self.comments_hits.clear();
+                    self.comments_ids.clear();
+                    let message = if available {
+                        "No comments, yet."
+                    } else {
+                        "Comments are not available for this story."
+                    };
+                    header
+                        .into_iter()
+                        .map(|line| prepend_string(&line, &" ".repeat(margin)))
+                        .chain([message.to_string()])
+                        .collect::<Vec<String>>()
+                        .join("\n")
                 }
             }
+            ViewMode::Reader => {
+                let margin = 2;
+                let width = (width as usize).saturating_sub(margin * 2);
+                let story = match self.get_selected_story() {
+                    Some(story) => story,
+                    None => return "No story selected.".to_string(),
+                };
+                let mut lines = wrap(story.title(), width)
+                    .into_iter()
+                    .map(|line| style(line).bold().to_string())
+                    .collect::<Vec<String>>();
+                match &self.article {
+                    Some(read) if read.story_url == *story.url() => {
+                        lines.push(style(&read.url).dim().to_string());
+                        if let (Some(reason), Some(archive)) = (&read.fallback_reason, read.archive)
+                        {
+                            let note =
+                                format!("{reason}, reading the copy from {}", archive.name());
+                            lines.extend(
+                                wrap(&note, width)
+                                    .into_iter()
+                                    .map(|line| style(line).yellow().to_string()),
+                            );
+                        }
+                        lines.push(String::new());
+                        if read.article.is_empty() {
+                            lines.push(
+                                "No readable text found, press o to open the story in the browser."
+                                    .to_string(),
+                            );
+                        } else {
+                            lines.extend(read.article.to_lines(width));
+                        }
+                    }
+                    _ => {
+                        lines.push(style(story.url()).dim().to_string());
+                        lines.push(String::new());
+                        lines.push("Loading…".to_string());
+                    }
+                }
+                let lines = lines
+                    .into_iter()
+                    .map(|line| {
+                        let line = prepend_string(&line, &" ".repeat(margin));
+                        match &self.search {
+                            Some(search) => search.highlight(&line),
+                            None => line,
+                        }
+                    })
+                    .collect::<Vec<String>>();
+
+                self.reader_hits = match &self.search {
+                    Some(search) => lines
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, line)| search.matches_escaped(line))
+                        .map(|(idx, _)| idx)
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                let height = height as usize;
+                self.reader_lines = lines.len();
+                self.reader_pos = self.reader_pos.min(lines.len().saturating_sub(height));
+                let end = lines.len().min(self.reader_pos + height);
+                lines[self.reader_pos..end].join("\n")
+            }
+        }
+    }
+
+    /// Scrolls the comments view to the comment with the short id `short_id` once the comments
+    /// are rendered.
+    pub(crate) fn focus_comment(&mut self, short_id: &str) {
+        self.comments_focus = Some(short_id.to_string());
+    }
+
+    /// Returns the comment at the top of the comments view, if the comments are open.
+    pub(crate) fn selected_comment(&self) -> Option<&Comment> {
+        match self.mode {
+            ViewMode::List | ViewMode::Reader => None,
+            ViewMode::Comments => {
+                // The lines above the comments do not belong to one.
+                let short_id = self.comments_ids[self.comments_pos.min(self.comments_ids.len())..]
+                    .iter()
+                    .find(|short_id| !short_id.is_empty())?;
+                self.get_selected_story()?.find_comment(short_id)
+            }
+        }
+    }
+
+    /// Sets the active search, or clears it when `search` is `None`, and jumps to the first hit.
+    pub(crate) fn search(&mut self, search: Option<Search>) {
+        self.search = search;
+        if self.search.is_some() {
+            match self.mode {
+                // Include the current story in the first jump.
+                ViewMode::List => {
+                    if !self.search_matches_story(self.pages.pos) {
+                        self.search_next(true)
+                    }
+                }
+                // The hits are only known after the next render, so wait for `n` there.
+                ViewMode::Comments => self.comments_hits.clear(),
+                ViewMode::Reader => self.reader_hits.clear(),
+            }
+        }
+    }
+
+    /// Returns the user whose profile can be opened from the view: the submitter of the selected
+    /// story in the list and the reader, or the author of the topmost comment in view in the
+    /// comments view.
+    pub(crate) fn selected_user(&self) -> Option<&str> {
+        match self.mode {
+            ViewMode::List | ViewMode::Reader => self.get_selected_story()?.byline().user(),
+            ViewMode::Comments => self.selected_comment().map(Comment::author),
         }
     }
 
-    pub(crate) fn pos(&self) -> usize {
-        self.list_pos
+    fn search_matches_story(&self, pos: usize) -> bool {
+        match (&self.search, self.get_story(pos)) {
+            (Some(search), Some(story)) => story.matches(search),
+            _ => false,
+        }
     }
 
-    pub(crate) fn view_page(&self) -> usize {
-        self.list_pos / self.page_size
+    /// Jumps to the next (or previous, if `forward` is false) search hit.
+    ///
+    /// In the list, all loaded stories are searched and the search wraps around. In the
+    /// comments view and the reader, the hits from the last render are used.
+    pub(crate) fn search_next(&mut self, forward: bool) {
+        if self.search.is_none() {
+            return;
+        }
+        match self.mode {
+            ViewMode::List => {
+                let len = self.len();
+                let hit = (1..=len)
+                    .map(|offset| {
+                        if forward {
+                            (self.pages.pos + offset) % len
+                        } else {
+                            (self.pages.pos + len - offset) % len
+                        }
+                    })
+                    .find(|&pos| self.search_matches_story(pos));
+                if let Some(pos) = hit {
+                    self.pages.pos = pos;
+                    self.comments_pos = 0;
+                    self.fit_page();
+                }
+            }
+            ViewMode::Comments => {
+                if let Some(line) = next_hit(&self.comments_hits, self.comments_pos, forward) {
+                    self.comments_pos = line;
+                }
+            }
+            ViewMode::Reader => {
+                if let Some(line) = next_hit(&self.reader_hits, self.reader_pos, forward) {
+                    self.reader_pos = line;
+                }
+            }
+        }
     }
 
     /// Returns the number of the site page the story under the position of the view can be found
@@ -167,7 +767,54 @@ impl View {
     ///
     /// The site page number is 1-indexed.
     pub(crate) fn site_page(&self) -> usize {
-        self.list_pos / STORIES_PER_SITE_PAGE + 1
+        // The filter may leave out stories, so go by the position among all loaded stories.
+        let idx = self.listed.get(self.pages.pos).copied().unwrap_or(0);
+        idx / self.source.per_page(&self.listing) + 1
+    }
+
+    /// Returns the contents of the status bar for this view.
+    pub(crate) fn status(&self) -> Status {
+        Status {
+            position: Some((self.pages.pos, self.len())),
+            page: Some(self.site_page()),
+            loaded_at: self.loaded_at,
+            error: self.error.as_ref().map(|err| format!("{err} (r to retry)")),
+            filter: {
+                let parts = self
+                    .filter
+                    .iter()
+                    .map(|filter| format!("filter {}", filter.query()))
+                    .chain(
+                        self.search
+                            .iter()
+                            .map(|search| format!("/{}", search.query())),
+                    )
+                    .collect::<Vec<String>>();
+                (!parts.is_empty()).then(|| parts.join(" · "))
+            },
+            // Muted stories are left out, as their titles are not shown.
+            watched: self
+                .listed
+                .iter()
+                .map(|&idx| &self.stories[idx])
+                .filter(|story| words::story_watched(story) && mutes::story_muted(story).is_none())
+                .count(),
+            ..Status::new(match &self.source {
+                Source::Lobsters => self.listing.to_string(),
+                // A feed only has the one list of items, and the merged front page is made of
+                // front pages.
+                Source::Feed(_) | Source::Merged => self.source.to_string(),
+                source => format!("{source} {}", self.listing),
+            })
+        }
+    }
+
+    pub(crate) fn source(&self) -> &Source {
+        &self.source
+    }
+
+    pub(crate) fn listing(&self) -> &Listing {
+        &self.listing
     }
 
     pub(crate) fn view_list(&mut self) {
@@ -180,16 +827,190 @@ impl View {
 
     pub(crate) fn view_toggle(&mut self) {
         self.mode = match self.mode {
-            ViewMode::List => ViewMode::Comments,
+            ViewMode::List | ViewMode::Reader => ViewMode::Comments,
             ViewMode::Comments => ViewMode::List,
         }
     }
 
-    pub(crate) fn mode(&self) -> ViewMode {
-        self.mode
+    /// Switches the selected story to its discussion on the next of the sources it is on, and
+    /// opens the comments.
+    pub(crate) fn next_discussion(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let story = self.get_selected_story_mut().ok_or("no story selected")?;
+        if !story.next_discussion() {
+            return Err(format!("this story is only on {}", story.source()).into());
+        }
+        self.comments_pos = 0;
+        self.mode = ViewMode::Comments;
+        Ok(())
+    }
+
+    /// Mutes the user returned by [`View::selected_user`], or unmutes them when they are muted.
+    pub(crate) fn toggle_mute_user(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let user = self
+            .selected_user()
+            .filter(|user| !user.is_empty())
+            .ok_or("no user selected")?
+            .to_string();
+        mutes::toggle_user(&user)?;
+        self.fit_page();
+        Ok(())
+    }
+
+    /// Mutes the domain of the selected story, or unmutes it when it is muted.
+    pub(crate) fn toggle_mute_domain(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let domain = self
+            .get_selected_story()
+            .and_then(|story| story.domain())
+            .ok_or("the selected story has no domain")?
+            .to_string();
+        mutes::toggle_domain(&domain)?;
+        self.fit_page();
+        Ok(())
+    }
+
+    /// Shows the selected muted story in full, or the topmost muted comment in the comments view,
+    /// or collapses it again when it is shown.
+    pub(crate) fn toggle_muted(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let key = match self.mode {
+            ViewMode::List | ViewMode::Reader => self
+                .get_selected_story()
+                .filter(|story| mutes::story_muted(story).is_some())
+                .map(|story| story.url().clone())
+                .ok_or("the selected story is not muted")?,
+            ViewMode::Comments => {
+                let story = self.get_selected_story().ok_or("no story selected")?;
+                self.comments_ids
+                    .iter()
+                    .skip(self.comments_pos)
+                    .take(self.height as usize)
+                    .filter_map(|short_id| story.find_comment(short_id))
+                    .find(|comment| mutes::comment_muted(comment))
+                    .map(|comment| comment.short_id().to_string())
+                    .ok_or("there is no muted comment in view")?
+            }
+        };
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+        self.fit_page();
+        Ok(())
+    }
+
+    /// Opens the article of the selected story in the reader, or goes back to the list if it is
+    /// open already.
+    pub(crate) fn view_reader_toggle(&mut self) {
+        self.reader_archive = None;
+        self.mode = match self.mode {
+            ViewMode::List | ViewMode::Comments => ViewMode::Reader,
+            ViewMode::Reader => ViewMode::List,
+        }
+    }
+}
+
+/// Returns the lines shown above the comments of `story`, at most `width` columns wide. For a story
+/// that is discussed in several places, these tell where the shown discussion is, and link to the
+/// others. They also tell when other discussions could not be looked up.
+fn comments_header(story: &Story, width: usize) -> Vec<String> {
+    let dim = |text: &str| {
+        wrap(text, width)
+            .into_iter()
+            .map(|line| style(line).dim().to_string())
+            .collect::<Vec<String>>()
+    };
+    let mut lines = Vec::new();
+    if let Some(err) = story.discussions_error() {
+        lines.extend(dim(&format!(
+            "Other discussions could not be looked up: {err}"
+        )));
+    }
+    if !story.others().is_empty() {
+        lines.extend(dim(&format!(
+            "Discussion on {}, also discussed here (d for the next one):",
+            story.source()
+        )));
+    }
+    for other in story.others() {
+        let mut details = Vec::new();
+        if let Some(votes) = other.votes() {
+            details.push(format!("{votes} points"));
+        }
+        if let Some(number) = other.comments_number() {
+            details.push(format!(
+                "{number} comment{}",
+                if number == 1 { "" } else { "s" }
+            ));
+        }
+        if !other.time().is_empty() {
+            details.push(other.time().to_string());
+        }
+        let link = other.comments_url().unwrap_or(other.url());
+        let line = format!(
+            "[{}] {} {}",
+            other.source().badge(),
+            details.join(", "),
+            style(link).underlined()
+        );
+        for (idx, line) in wrap_hanging(&line, width, 2).into_iter().enumerate() {
+            lines.push(if idx == 0 { format!("  {line}") } else { line });
+        }
+    }
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// A page that was read: the url it was read from, and its document if it is an HTML document.
+type Page = (String, Option<Html>);
+
+/// A page that was read, with the archive it was read from instead of its site and why.
+type ArchivedPage = (Option<Archive>, Option<String>, Page);
+
+/// Gets the page at `url`, or its copy from `archive`. Returns the url that was read, and the page
+/// if it is an HTML document.
+fn read_page(url: &str, archive: Option<Archive>) -> Result<Page, Box<dyn std::error::Error>> {
+    let url = match archive {
+        Some(archive) => archive.url(url),
+        None => url.to_string(),
+    };
+    let html = get_document(url.clone())?;
+    Ok((url, html))
+}
+
+/// Gets the page at `url`, or the first archived copy of it that can be read when the site
+/// responds with an error status. Returns the archive and the error of the site when a copy was
+/// read instead, and the url that was read with its page.
+fn read_page_or_archived(url: &str) -> Result<ArchivedPage, Box<dyn std::error::Error>> {
+    match read_page(url, None) {
+        Ok(page) => Ok((None, None, page)),
+        Err(err) if is_status_error(err.as_ref()) => {
+            let archived = Archive::ALL
+                .into_iter()
+                .find_map(|archive| Some((archive, read_page(url, Some(archive)).ok()?)));
+            match archived {
+                Some((archive, page)) => Ok((Some(archive), Some(err.to_string()), page)),
+                None => Err(err),
+            }
+        }
+        Err(err) => Err(err),
     }
 }
 
+/// Returns the first of the `hits` after `pos`, or before it if not `forward`, wrapping around.
+fn next_hit(hits: &[usize], pos: usize, forward: bool) -> Option<usize> {
+    let hit = if forward {
+        hits.iter()
+            .find(|&&line| line > pos)
+            .or_else(|| hits.first())
+    } else {
+        hits.iter()
+            .rev()
+            .find(|&&line| line < pos)
+            .or_else(|| hits.last())
+    };
+    hit.copied()
+}
+
 pub(crate) enum Travel {
     NextStep,
     PrevStep,
@@ -198,3 +1019,85 @@ pub(crate) enum Travel {
     Top,
     Bottom,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::{SiteSearch, What};
+    use crate::testing::{fixture, stand_in};
+
+    const ARTICLE: &str = "<html><body><article><p>The article.</p></article></body></html>";
+
+    #[test]
+    fn stories_with_a_mute_word_are_not_listed() {
+        // The stand-in has "crypto" as a mute word.
+        let page = fixture("search_stories.html").replace(
+            ">Borrowing without tears<",
+            ">Borrowing crypto without tears<",
+        );
+        stand_in().serve_page("/search?q=muted&what=stories&order=newest&page=1", &page);
+        let search = SiteSearch::parse("muted order:newest", What::Stories).unwrap();
+        let mut view = View::with_listing(Listing::Search(search));
+        view.load_stories().unwrap();
+        assert_eq!(view.stories.len(), 2);
+        assert_eq!(view.len(), 1);
+        assert_eq!(
+            view.get_story(0).unwrap().title(),
+            "What are you doing in Rust this week?"
+        );
+
+        // Clearing the filter lists the stories again, which still leaves the muted one out.
+        view.set_filter(None);
+        assert_eq!(view.len(), 1);
+    }
+
+    #[test]
+    fn reads_the_archived_copy_of_a_page_with_an_error() {
+        let gone = stand_in().url("/article/gone");
+        stand_in().serve("/article/gone", 404, "text/html", "Not Found");
+        stand_in().serve_page(&format!("/wayback/{gone}"), ARTICLE);
+        let (archive, reason, (url, html)) = read_page_or_archived(&gone).unwrap();
+        assert_eq!(archive, Some(Archive::Wayback));
+        assert!(reason.unwrap().contains("404"));
+        assert_eq!(url, Archive::Wayback.url(&gone));
+        assert!(html.is_some());
+
+        // The next archive is tried when the first has no copy.
+        let broken = stand_in().url("/article/broken");
+        stand_in().serve("/article/broken", 500, "text/html", "Internal Server Error");
+        stand_in().serve_page(&format!("/today/newest/{broken}"), ARTICLE);
+        let (archive, reason, (url, _)) = read_page_or_archived(&broken).unwrap();
+        assert_eq!(archive, Some(Archive::ArchiveToday));
+        assert!(reason.unwrap().contains("500"));
+        assert_eq!(url, Archive::ArchiveToday.url(&broken));
+    }
+
+    #[test]
+    fn reads_a_page_without_an_error_from_its_site() {
+        let url = stand_in().url("/article/fine");
+        stand_in().serve_page("/article/fine", ARTICLE);
+        let (archive, reason, (read_url, _)) = read_page_or_archived(&url).unwrap();
+        assert_eq!((archive, reason, read_url), (None, None, url));
+    }
+
+    #[test]
+    fn keeps_the_error_without_an_archived_copy() {
+        let missing = stand_in().url("/article/missing");
+        stand_in().serve("/article/missing", 410, "text/html", "Gone");
+        let err = read_page_or_archived(&missing).unwrap_err();
+        assert!(err.to_string().contains("410"));
+        assert!(
+            stand_in()
+                .requests_to(&format!("/today/newest/{missing}"))
+                .len()
+                == 1
+        );
+
+        // Only an error status falls back, not a failure to connect.
+        let unreachable = "http://127.0.0.1:1/article";
+        assert!(read_page_or_archived(unreachable).is_err());
+        assert!(stand_in()
+            .requests_to(&format!("/wayback/{unreachable}"))
+            .is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+//! Watch words, which are highlighted wherever they show up, and mute words, which hide the stories
+//! with them in the title from the list.
+//!
+//! A word, or a phrase, is matched regardless of case, and only as a whole word: `go` does not
+//! match `good`. An entry between slashes, such as `/wasm(time)?/`, is a regular expression
+//! instead, which is matched as it is written.
+
+use regex::Regex;
+
+use crate::config::WordsConfig;
+use crate::search::visible_chars;
+use crate::settings::settings;
+use crate::story::Story;
+
+/// Highlighting for watch words, which leaves bold and dim text as it is. The colour and underline
+/// that were set before a watch word are set again after it.
+const WATCH_START: &str = "\u{1b}[33;4m";
+
+/// The watch and mute words.
+#[derive(Debug, Default)]
+pub(crate) struct Words {
+    /// All watch words in one expression, if there are any.
+    watch: Option<Regex>,
+    /// All mute words in one expression, if there are any.
+    mute: Option<Regex>,
+}
+
+impl Words {
+    /// Compiles the watch and mute words from the configuration.
+    pub(crate) fn new(config: &WordsConfig) -> Result<Self, String> {
+        Ok(Self {
+            watch: compile(&config.watch, "watch")?,
+            mute: compile(&config.mute, "mute")?,
+        })
+    }
+}
+
+fn words() -> &'static Words {
+    &settings().words
+}
+
+/// Compiles `entries` into a single expression that matches any of them.
+fn compile(entries: &[String], kind: &str) -> Result<Option<Regex>, String> {
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    let patterns = entries
+        .iter()
+        .map(|entry| pattern(entry).map_err(|err| format!("invalid {kind} word {entry:?}: {err}")))
+        .collect::<Result<Vec<String>, String>>()?;
+    Regex::new(&patterns.join("|"))
+        .map(Some)
+        .map_err(|err| format!("invalid {kind} words: {err}"))
+}
+
+/// Returns the pattern for an entry, which is a regular expression between slashes or a word.
+fn pattern(entry: &str) -> Result<String, String> {
+    if let Some(regex) = entry
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+    {
+        if regex.is_empty() {
+            return Err("the expression is empty".to_string());
+        }
+        Regex::new(regex).map_err(|err| err.to_string())?;
+        return Ok(format!("(?:{regex})"));
+    }
+    let word = entry.trim();
+    if word.is_empty() {
+        return Err("the word is empty".to_string());
+    }
+    // A word boundary only makes sense next to a word character, which `c++` does not end with.
+    let boundary = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => "",
+    };
+    Ok(format!(
+        "(?i:{}{}{})",
+        boundary(word.chars().next()),
+        regex::escape(word),
+        boundary(word.chars().last())
+    ))
+}
+
+/// Returns whether `text` contains a watch word.
+fn is_watched(text: &str) -> bool {
+    words()
+        .watch
+        .as_ref()
+        .is_some_and(|watch| watch.is_match(text))
+}
+
+/// Returns whether the title or a tag of `story` contains a watch word.
+pub(crate) fn story_watched(story: &Story) -> bool {
+    is_watched(story.title()) || story.tags().iter().any(|tag| is_watched(tag.name()))
+}
+
+/// Returns the first mute word in `title`, as it is written there.
+pub(crate) fn muted_word(title: &str) -> Option<&str> {
+    Some(words().mute.as_ref()?.find(title)?.as_str())
+}
+
+/// Highlights the watch words in the visible text of `line`. Escape sequences that already exist in
+/// the line are left in place.
+pub(crate) fn highlight(line: &str) -> String {
+    match &words().watch {
+        Some(watch) => highlight_with(watch, line),
+        None => line.to_string(),
+    }
+}
+
+/// Highlights the matches of `watch` in the visible text of `line`.
+fn highlight_with(watch: &Regex, line: &str) -> String {
+    let visible = visible_chars(line);
+    let text = visible.iter().map(|(_, c)| c).collect::<String>();
+    // The byte offset in `text` of every visible character, by its index in `visible`.
+    let offsets = text
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .collect::<Vec<usize>>();
+    let ranges = watch
+        .find_iter(&text)
+        .filter(|found| !found.as_str().is_empty())
+        .map(|found| {
+            let first = offsets.partition_point(|&offset| offset < found.start());
+            let last = offsets.partition_point(|&offset| offset < found.end()) - 1;
+            let (last_idx, last_char) = visible[last];
+            (visible[first].0, last_idx + last_char.len_utf8())
+        })
+        .collect::<Vec<(usize, usize)>>();
+    let mut marked = String::with_capacity(line.len());
+    let mut prev = 0;
+    for (start, end) in ranges {
+        marked.push_str(&line[prev..start]);
+        marked.push_str(WATCH_START);
+        marked.push_str(&line[start..end]);
+        marked.push_str(&restore_style(&line[..end]));
+        prev = end;
+    }
+    marked.push_str(&line[prev..]);
+    marked
+}
+
+/// Returns the escape sequence that sets the foreground colour and underline again to how the
+/// escape sequences in `text` leave them.
+fn restore_style(text: &str) -> String {
+    let mut foreground = "39".to_string();
+    let mut underline = "24";
+    for sequence in text.split('\u{1b}').skip(1) {
+        let params = match sequence
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once('m'))
+        {
+            // Only sequences that set graphics are of interest, and they have only parameters.
+            Some((params, _)) if params.bytes().all(|b| b.is_ascii_digit() || b == b';') => params,
+            _ => continue,
+        };
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            match param {
+                "" | "0" => {
+                    foreground = "39".to_string();
+                    underline = "24";
+                }
+                "4" => underline = "4",
+                "24" => underline = "24",
+                _ if matches!(param.parse(), Ok(30..=37 | 39 | 90..=97)) => {
+                    foreground = param.to_string()
+                }
+                // Extended colours are followed by the index of the colour or its components.
+                "38" | "48" => {
+                    let colour = match params.next() {
+                        Some("5") => params.next().map(|idx| format!("5;{idx}")),
+                        Some("2") => {
+                            let rgb = params.by_ref().take(3).collect::<Vec<&str>>();
+                            Some(format!("2;{}", rgb.join(";")))
+                        }
+                        _ => None,
+                    };
+                    if let (Some(colour), "38") = (colour, param) {
+                        foreground = format!("38;{colour}");
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    format!("\u{1b}[{foreground};{underline}m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(words: &[&str]) -> Regex {
+        let words = words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<String>>();
+        compile(&words, "watch").unwrap().unwrap()
+    }
+
+    #[test]
+    fn highlights_whole_words() {
+        let watch = watch(&["go", "/wasm(time)?/"]);
+        assert_eq!(
+            highlight_with(&watch, "Go is good"),
+            "\u{1b}[33;4mGo\u{1b}[39;24m is good"
+        );
+        assert_eq!(
+            highlight_with(&watch, "wasmtime and wasm"),
+            "\u{1b}[33;4mwasmtime\u{1b}[39;24m and \u{1b}[33;4mwasm\u{1b}[39;24m"
+        );
+    }
+
+    #[test]
+    fn restores_the_colour_after_a_watch_word() {
+        let watch = watch(&["rust"]);
+        let tag = "\u{1b}[38;5;94mrust-lang\u{1b}[0m";
+        assert_eq!(
+            highlight_with(&watch, tag),
+            "\u{1b}[38;5;94m\u{1b}[33;4mrust\u{1b}[38;5;94;24m-lang\u{1b}[0m"
+        );
+        let linked = "see \u{1b}[31m\u{1b}[4mrust\u{1b}[24m docs";
+        assert_eq!(
+            highlight_with(&watch, linked),
+            "see \u{1b}[31m\u{1b}[4m\u{1b}[33;4mrust\u{1b}[31;4m\u{1b}[24m docs"
+        );
+    }
+
+    #[test]
+    fn highlighting_carries_over_a_line_break() {
+        let watch = watch(&["async runtime"]);
+        let text = highlight_with(&watch, "an async runtime");
+        assert_eq!(
+            crate::wrap::wrap(&text, 9),
+            [
+                "an \u{1b}[33;4masync\u{1b}[0m",
+                "\u{1b}[33;4mruntime\u{1b}[39;24m\u{1b}[0m"
+            ]
+        );
+    }
+
+    #[test]
+    fn style_after_escape_sequences() {
+        assert_eq!(restore_style("plain"), "\u{1b}[39;24m");
+        assert_eq!(restore_style("\u{1b}[1;32;4mbold"), "\u{1b}[32;4m");
+        assert_eq!(restore_style("\u{1b}[92m\u{1b}[0m"), "\u{1b}[39;24m");
+        assert_eq!(
+            restore_style("\u{1b}[38;2;1;2;3m\u{1b}[48;5;17m"),
+            "\u{1b}[38;2;1;2;3;24m"
+        );
+        // Other escape sequences leave the style as it is.
+        assert_eq!(restore_style("\u{1b}[35m\u{1b}[2K"), "\u{1b}[35;24m");
+    }
+}
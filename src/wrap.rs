@@ -0,0 +1,228 @@
+//! Wrapping of text that may contain ANSI escape sequences to a number of terminal columns.
+//!
+//! Widths are measured in the columns the text takes up on the terminal, so wide characters (CJK,
+//! emoji) count for two columns and combining characters for none. Lines are broken between
+//! words where possible, and words that are too long for a line by themselves are broken between
+//! characters. Styling that is active at a line break is reset at the end of the line and started
+//! again at the beginning of the next, such that every line can be printed on its own.
+
+use unicode_width::UnicodeWidthChar;
+
+/// Wraps `text` to lines of at most `width` columns.
+///
+/// Line breaks that are already in the text are kept.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    wrap_hanging(text, width, 0)
+}
+
+/// Wraps `text` with a hanging indent of `indent` columns.
+///
+/// The first line is assumed to be preceded by something `indent` columns wide, such as a label or
+/// a number, and all following lines are indented by as many spaces. Every line holds at most
+/// `width - indent` columns of text.
+pub(crate) fn wrap_hanging(text: &str, width: usize, indent: usize) -> Vec<String> {
+    let mut wrapper = Wrapper::new(width.saturating_sub(indent).max(1), " ".repeat(indent));
+    for (idx, paragraph) in text.lines().enumerate() {
+        if idx > 0 {
+            wrapper.break_line();
+        }
+        for word in paragraph.split_whitespace() {
+            wrapper.push_word(word);
+        }
+    }
+    wrapper.finish()
+}
+
+/// Splits `text` into its escape sequences and the characters between them.
+fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '\u{1b}' {
+            let mut end = start + c.len_utf8();
+            // A CSI sequence ends at the first character in the range '@'..='~'.
+            if let Some(&(_, '[')) = chars.peek() {
+                for (idx, c) in chars.by_ref() {
+                    end = idx + c.len_utf8();
+                    if c != '[' && ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            tokens.push(Token::Escape(&text[start..end]));
+        } else {
+            tokens.push(Token::Char(c));
+        }
+    }
+    tokens
+}
+
+enum Token<'a> {
+    Escape(&'a str),
+    Char(char),
+}
+
+/// The number of columns `c` takes up on the terminal.
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+struct Wrapper {
+    width: usize,
+    indent: String,
+    lines: Vec<String>,
+    line: String,
+    line_width: usize,
+    /// The escape sequences that set the styling that is active at the end of the line.
+    active: Vec<String>,
+}
+
+impl Wrapper {
+    fn new(width: usize, indent: String) -> Self {
+        Self {
+            width,
+            indent,
+            lines: Vec::new(),
+            line: String::new(),
+            line_width: 0,
+            active: Vec::new(),
+        }
+    }
+
+    /// Keeps track of the styling that is active after the escape sequence `escape`.
+    fn apply_escape(&mut self, escape: &str) {
+        match escape {
+            "\u{1b}[0m" | "\u{1b}[m" => self.active.clear(),
+            e if e.ends_with('m') => self.active.push(e.to_string()),
+            _ => {}
+        }
+    }
+
+    fn break_line(&mut self) {
+        if !self.active.is_empty() {
+            self.line.push_str("\u{1b}[0m");
+        }
+        let line = std::mem::take(&mut self.line);
+        self.lines.push(line);
+        self.line = format!("{}{}", self.indent, self.active.concat());
+        self.line_width = 0;
+    }
+
+    fn push_word(&mut self, word: &str) {
+        let word_width = console::measure_text_width(word);
+        if self.line_width > 0 && self.line_width + 1 + word_width > self.width {
+            self.break_line();
+        }
+        if self.line_width > 0 {
+            self.line.push(' ');
+            self.line_width += 1;
+        }
+
+        for token in tokens(word) {
+            match token {
+                Token::Escape(escape) => {
+                    self.line.push_str(escape);
+                    self.apply_escape(escape);
+                }
+                Token::Char(c) => {
+                    // Only words that do not fit on a line by themselves are broken up here.
+                    let w = char_width(c);
+                    if self.line_width > 0 && self.line_width + w > self.width {
+                        self.break_line();
+                    }
+                    self.line.push(c);
+                    self.line_width += w;
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.active.is_empty() {
+            self.line.push_str("\u{1b}[0m");
+        }
+        self.lines.push(self.line);
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_between_words() {
+        assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(wrap("  spaced   out  ", 20), ["spaced out"]);
+        assert_eq!(wrap("", 10), [""]);
+    }
+
+    #[test]
+    fn keeps_line_breaks() {
+        assert_eq!(wrap("one\n\ntwo three", 5), ["one", "", "two", "three"]);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(wrap("日本語 テキスト", 10), ["日本語", "テキスト"]);
+        assert_eq!(wrap("日本語", 5), ["日本", "語"]);
+        assert_eq!(wrap("🦀🦀🦀", 4), ["🦀🦀", "🦀"]);
+        assert_eq!(wrap("a 🦀", 4), ["a 🦀"]);
+        assert_eq!(wrap("ab 🦀", 4), ["ab", "🦀"]);
+    }
+
+    #[test]
+    fn combining_characters_take_no_columns() {
+        let word = "e\u{301}e\u{301}e\u{301}";
+        assert_eq!(wrap(word, 3), [word]);
+        // The accent stays with the letter it is on.
+        assert_eq!(wrap(word, 2), ["e\u{301}e\u{301}", "e\u{301}"]);
+    }
+
+    #[test]
+    fn breaks_words_longer_than_the_line() {
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("a abcdefghij b", 4), ["a", "abcd", "efgh", "ij b"]);
+        assert_eq!(wrap("abc", 0), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn escape_sequences_take_no_columns() {
+        let text = "\u{1b}[1mbold\u{1b}[0m text";
+        assert_eq!(wrap(text, 9), [text]);
+    }
+
+    #[test]
+    fn carries_styling_across_a_break() {
+        assert_eq!(
+            wrap("\u{1b}[1mbold words\u{1b}[0m here", 5),
+            ["\u{1b}[1mbold\u{1b}[0m", "\u{1b}[1mwords\u{1b}[0m", "here"]
+        );
+        assert_eq!(
+            wrap("\u{1b}[1m\u{1b}[3mabcdef", 3),
+            [
+                "\u{1b}[1m\u{1b}[3mabc\u{1b}[0m",
+                "\u{1b}[1m\u{1b}[3mdef\u{1b}[0m"
+            ]
+        );
+        // Styling ends with the line break it was reset before.
+        assert_eq!(
+            wrap("\u{1b}[2mdim\u{1b}[0m\nplain", 10),
+            ["\u{1b}[2mdim\u{1b}[0m", "plain"]
+        );
+    }
+
+    #[test]
+    fn hanging_indent() {
+        assert_eq!(
+            wrap_hanging("one two three four", 10, 4),
+            ["one", "    two", "    three", "    four"]
+        );
+        assert_eq!(
+            wrap_hanging("\u{1b}[4mlinked text", 8, 2),
+            ["\u{1b}[4mlinked\u{1b}[0m", "  \u{1b}[4mtext\u{1b}[0m"]
+        );
+        // The text still gets a column when the indent takes up the whole width.
+        assert_eq!(wrap_hanging("ab", 2, 4), ["a", "    b"]);
+    }
+}